@@ -0,0 +1,645 @@
+use crate::error::CustomError;
+use base64::Engine;
+use std::str;
+
+/// Encoding of the `data` passed to `Detective::matches_encoded()`.
+///
+/// Non-JSON payloads are transcoded to JSON before matching so that paths
+/// and matchers behave exactly as they do for JSON input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Detect the format from the payload itself (see `sniff()`)
+    Auto,
+    Json,
+    Yaml,
+    /// `application/x-www-form-urlencoded`
+    Form,
+    /// Raw HTTP/1.x request or response text (see `http_to_json()`)
+    Http,
+    /// `multipart/form-data` body (see `multipart_to_json()`)
+    Multipart,
+    /// gRPC metadata as `key: value` lines (see `grpc_metadata_to_json()`).
+    /// Never sniffed as it's indistinguishable from simple YAML.
+    GrpcMetadata,
+    /// CBOR (RFC 8949), see `cbor_to_json()`. Binary, so never sniffed.
+    Cbor,
+    /// MessagePack, see `msgpack_to_json()`. Binary, so never sniffed.
+    MessagePack,
+}
+
+/// Conservatively guess the format of `data`.
+///
+/// - leading `{` or `[` -> JSON
+/// - an HTTP/1.x request or status line -> HTTP
+/// - a `--boundary` line followed by a `Content-Disposition` header -> multipart
+/// - leading `---` or a first line that looks like `key: value` -> YAML
+/// - a single line of `key=value` pairs joined by `&` -> form
+///
+/// Returns `None` when the payload doesn't clearly look like any of these.
+pub fn sniff(data: &[u8]) -> Option<DataFormat> {
+    let data_as_str = str::from_utf8(data).ok()?;
+    let trimmed = data_as_str.trim_start_matches('\u{feff}').trim();
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Some(DataFormat::Json);
+    }
+
+    let first_line = trimmed.lines().next().unwrap_or_default();
+
+    if is_http_start_line(first_line) {
+        return Some(DataFormat::Http);
+    }
+
+    if is_multipart_start(trimmed) {
+        return Some(DataFormat::Multipart);
+    }
+
+    if trimmed.starts_with("---") {
+        return Some(DataFormat::Yaml);
+    }
+
+    if let Some((key, _)) = first_line.split_once(':') {
+        let key = key.trim();
+
+        if !key.is_empty() && !key.contains(['=', '&', ' ']) {
+            return Some(DataFormat::Yaml);
+        }
+    }
+
+    if !trimmed.contains(char::is_whitespace)
+        && trimmed
+            .split('&')
+            .all(|pair| matches!(pair.split_once('='), Some((k, _)) if !k.is_empty()))
+    {
+        return Some(DataFormat::Form);
+    }
+
+    None
+}
+
+/// Transcode `data` into a JSON document. `DataFormat::Auto` is resolved via `sniff()`.
+pub fn to_json(data: &[u8], format: DataFormat) -> Result<String, CustomError> {
+    let format = match format {
+        DataFormat::Auto => sniff(data).ok_or_else(|| {
+            CustomError::Error("unable to detect content type of data".to_string())
+        })?,
+        format => format,
+    };
+
+    match format {
+        DataFormat::Json => {
+            let data_as_str = str::from_utf8(data).map_err(|e| {
+                CustomError::Error(format!("unable to convert bytes to string: {}", e))
+            })?;
+
+            Ok(data_as_str.to_string())
+        }
+        DataFormat::Yaml => {
+            let value: serde_json::Value = serde_yaml::from_slice(data)
+                .map_err(|e| CustomError::Error(format!("unable to parse yaml: {}", e)))?;
+
+            Ok(value.to_string())
+        }
+        DataFormat::Form => Ok(form_to_json(data).to_string()),
+        DataFormat::Http => Ok(http_to_json(data)?.to_string()),
+        DataFormat::Multipart => Ok(multipart_to_json(data)?.to_string()),
+        DataFormat::GrpcMetadata => Ok(grpc_metadata_to_json(data)?.to_string()),
+        DataFormat::Cbor => {
+            let value: ciborium::Value = ciborium::from_reader(data)
+                .map_err(|e| CustomError::Error(format!("unable to parse cbor: {}", e)))?;
+
+            Ok(cbor_to_json(value).to_string())
+        }
+        DataFormat::MessagePack => Ok(msgpack_to_json(data)?.to_string()),
+        DataFormat::Auto => unreachable!("auto is resolved above"),
+    }
+}
+
+// Insert a value, collecting repeated keys into an array in the order they appear
+fn insert_repeated(
+    obj: &mut serde_json::Map<String, serde_json::Value>,
+    key: String,
+    value: serde_json::Value,
+) {
+    match obj.get_mut(&key) {
+        Some(serde_json::Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = serde_json::Value::Array(vec![first, value]);
+        }
+        None => {
+            obj.insert(key, value);
+        }
+    }
+}
+
+// Repeated keys are collected into an array, in the order they appear
+fn form_to_json(data: &[u8]) -> serde_json::Value {
+    let mut obj = serde_json::Map::new();
+
+    for (key, value) in form_urlencoded::parse(data) {
+        insert_repeated(&mut obj, key.into_owned(), serde_json::Value::String(value.into_owned()));
+    }
+
+    serde_json::Value::Object(obj)
+}
+
+fn is_http_start_line(line: &str) -> bool {
+    let line = line.trim_end();
+
+    if line.starts_with("HTTP/") {
+        return true;
+    }
+
+    match line.split(' ').collect::<Vec<&str>>()[..] {
+        [method, _, version] => {
+            !method.is_empty()
+                && method.bytes().all(|b| b.is_ascii_uppercase())
+                && version.starts_with("HTTP/")
+        }
+        _ => false,
+    }
+}
+
+/// Raw HTTP text is exposed as:
+///
+/// - `request.method`, `request.path`, `request.version` for a request line
+/// - `status.version`, `status.code` (number), `status.reason` for a status line
+/// - `headers.<lowercased name>` and `body`
+///
+/// Repeated headers (ie. `Set-Cookie`) are collected into an array.
+fn http_to_json(data: &[u8]) -> Result<serde_json::Value, CustomError> {
+    let data_as_str = str::from_utf8(data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    let (head, body) = data_as_str
+        .split_once("\r\n\r\n")
+        .or_else(|| data_as_str.split_once("\n\n"))
+        .unwrap_or((data_as_str, ""));
+
+    let mut lines = head.lines();
+    let start_line = lines.next().unwrap_or_default().trim();
+    let mut parts = start_line.splitn(3, ' ');
+
+    let mut obj = serde_json::Map::new();
+
+    if start_line.starts_with("HTTP/") {
+        let version = parts.next().unwrap_or_default();
+        let code: u16 = parts.next().unwrap_or_default().parse().map_err(|_| {
+            CustomError::Error(format!("invalid http status line: {}", start_line))
+        })?;
+
+        obj.insert(
+            "status".to_string(),
+            serde_json::json!({
+                "version": version,
+                "code": code,
+                "reason": parts.next().unwrap_or_default(),
+            }),
+        );
+    } else {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/") => {
+                obj.insert(
+                    "request".to_string(),
+                    serde_json::json!({
+                        "method": method,
+                        "path": path,
+                        "version": version,
+                    }),
+                );
+            }
+            _ => {
+                return Err(CustomError::Error(format!(
+                    "invalid http request line: {}",
+                    start_line
+                )))
+            }
+        }
+    }
+
+    let mut headers = serde_json::Map::new();
+
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            insert_repeated(
+                &mut headers,
+                name.trim().to_ascii_lowercase(),
+                serde_json::Value::String(value.trim().to_string()),
+            );
+        }
+    }
+
+    obj.insert("headers".to_string(), serde_json::Value::Object(headers));
+    obj.insert("body".to_string(), serde_json::Value::String(body.to_string()));
+
+    Ok(serde_json::Value::Object(obj))
+}
+
+fn is_multipart_start(data: &str) -> bool {
+    let mut lines = data.lines();
+
+    match (lines.next(), lines.next()) {
+        (Some(boundary), Some(header)) => {
+            boundary.starts_with("--")
+                && header
+                    .get(..20)
+                    .is_some_and(|h| h.eq_ignore_ascii_case("content-disposition:"))
+        }
+        _ => false,
+    }
+}
+
+/// Multipart parts are exposed by the `name` in their `Content-Disposition` header:
+///
+/// - `part.<name>.content`
+/// - `part.<name>.filename` (if given)
+/// - `part.<name>.content_type` (if given)
+///
+/// The boundary is taken from the first delimiter line of the body, so a
+/// `Content-Type` header isn't needed.
+fn multipart_to_json(data: &[u8]) -> Result<serde_json::Value, CustomError> {
+    let data_as_str = str::from_utf8(data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    let body = data_as_str.trim_start();
+    let first_line = body.lines().next().unwrap_or_default().trim_end();
+
+    if !first_line.starts_with("--") || first_line.len() <= 2 {
+        return Err(CustomError::Error(
+            "multipart body must start with a boundary delimiter".to_string(),
+        ));
+    }
+
+    let delimiter = first_line;
+    let mut parts = serde_json::Map::new();
+
+    // Everything after the final "--boundary--" is epilogue and ignored
+    for raw_part in body[delimiter.len()..].split(delimiter) {
+        if raw_part.starts_with("--") {
+            break;
+        }
+
+        let raw_part = raw_part.strip_prefix("\r\n").or_else(|| raw_part.strip_prefix('\n'));
+
+        let raw_part = match raw_part {
+            Some(raw_part) => raw_part,
+            None => continue,
+        };
+
+        let (head, content) = raw_part
+            .split_once("\r\n\r\n")
+            .or_else(|| raw_part.split_once("\n\n"))
+            .ok_or_else(|| CustomError::Error("multipart part is missing headers".to_string()))?;
+
+        // The line break before the next delimiter belongs to the delimiter
+        let content = content
+            .strip_suffix("\r\n")
+            .or_else(|| content.strip_suffix('\n'))
+            .unwrap_or(content);
+
+        let mut name = None;
+        let mut part = serde_json::Map::new();
+
+        for line in head.lines() {
+            let (header, value) = match line.split_once(':') {
+                Some((header, value)) => (header.trim().to_ascii_lowercase(), value.trim()),
+                None => continue,
+            };
+
+            match header.as_str() {
+                "content-disposition" => {
+                    name = header_param(value, "name");
+
+                    if let Some(filename) = header_param(value, "filename") {
+                        part.insert("filename".to_string(), serde_json::Value::String(filename));
+                    }
+                }
+                "content-type" => {
+                    part.insert(
+                        "content_type".to_string(),
+                        serde_json::Value::String(value.to_string()),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let name = name.ok_or_else(|| {
+            CustomError::Error("multipart part is missing a name".to_string())
+        })?;
+
+        part.insert("content".to_string(), serde_json::Value::String(content.to_string()));
+        parts.insert(name, serde_json::Value::Object(part));
+    }
+
+    Ok(serde_json::json!({ "part": parts }))
+}
+
+// Value of a `key=value` or `key="value"` parameter in a header like Content-Disposition
+fn header_param(header: &str, key: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|param| {
+        let (k, v) = param.trim().split_once('=')?;
+
+        if !k.trim().eq_ignore_ascii_case(key) {
+            return None;
+        }
+
+        let v = v.trim();
+        let v = v
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(v);
+
+        Some(v.replace("\\\"", "\""))
+    })
+}
+
+/// gRPC metadata is exposed as `metadata.<lowercased key>`. Values of `-bin`
+/// keys are base64 decoded first (invalid UTF-8 is replaced with U+FFFD) so
+/// matchers run on the decoded bytes. Repeated keys are collected into an array.
+fn grpc_metadata_to_json(data: &[u8]) -> Result<serde_json::Value, CustomError> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+
+    let data_as_str = str::from_utf8(data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    let mut metadata = serde_json::Map::new();
+
+    for line in data_as_str.lines().filter(|l| !l.trim().is_empty()) {
+        let (key, value) = line.split_once(':').ok_or_else(|| {
+            CustomError::Error(format!("invalid grpc metadata line: {}", line))
+        })?;
+
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+
+        let value = if key.ends_with("-bin") {
+            let decoded = STANDARD
+                .decode(value)
+                .or_else(|_| STANDARD_NO_PAD.decode(value))
+                .map_err(|e| {
+                    CustomError::Error(format!("invalid base64 in grpc metadata '{}': {}", key, e))
+                })?;
+
+            String::from_utf8_lossy(&decoded).into_owned()
+        } else {
+            value.to_string()
+        };
+
+        insert_repeated(&mut metadata, key, serde_json::Value::String(value));
+    }
+
+    Ok(serde_json::json!({ "metadata": metadata }))
+}
+
+/// CBOR values map onto JSON as follows:
+///
+/// - byte strings become standard base64 strings
+/// - tags are dropped in favour of the tagged value
+/// - integers outside of the i64/u64 range and non-finite floats become floats/null
+///
+/// Map keys don't have to be strings in CBOR. Text keys are used as-is, integer,
+/// float, bool and null keys by their JSON text (`{1: "a"}` is addressed as path
+/// `1`, `{true: "a"}` as `true`), byte string keys by their lowercase hex and
+/// array/map keys by their compact JSON. Keys that end up the same keep the last value.
+fn cbor_to_json(value: ciborium::Value) -> serde_json::Value {
+    use base64::engine::general_purpose::STANDARD;
+
+    match value {
+        ciborium::Value::Integer(i) => {
+            let i = i128::from(i);
+
+            match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => serde_json::Value::from(i),
+                (_, Ok(u)) => serde_json::Value::from(u),
+                _ => serde_json::Value::from(i as f64),
+            }
+        }
+        ciborium::Value::Bytes(bytes) => serde_json::Value::String(STANDARD.encode(bytes)),
+        // from(f64) maps NaN and infinities to null
+        ciborium::Value::Float(f) => serde_json::Value::from(f),
+        ciborium::Value::Text(text) => serde_json::Value::String(text),
+        ciborium::Value::Bool(b) => serde_json::Value::Bool(b),
+        ciborium::Value::Null => serde_json::Value::Null,
+        ciborium::Value::Tag(_, value) => cbor_to_json(*value),
+        ciborium::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(cbor_to_json).collect())
+        }
+        ciborium::Value::Map(entries) => serde_json::Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (cbor_key(key), cbor_to_json(value)))
+                .collect(),
+        ),
+        // ciborium::Value is non_exhaustive
+        _ => serde_json::Value::Null,
+    }
+}
+
+fn cbor_key(key: ciborium::Value) -> String {
+    match key {
+        ciborium::Value::Text(text) => text,
+        ciborium::Value::Bytes(bytes) => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        ciborium::Value::Tag(_, key) => cbor_key(*key),
+        key => cbor_to_json(key).to_string(),
+    }
+}
+
+/// Transcode a single MessagePack value into JSON, using the same mapping as
+/// `cbor_to_json()`:
+///
+/// - bin and ext values become standard base64 strings (the ext type is dropped)
+/// - non-finite floats become null
+/// - string keys are used as-is, bin keys by their lowercase hex and any other
+///   key by its compact JSON text
+///
+/// Truncated input, trailing bytes, strings that aren't UTF-8 and the reserved
+/// 0xc1 marker are errors.
+pub fn msgpack_to_json(data: &[u8]) -> Result<serde_json::Value, CustomError> {
+    let mut reader = MsgpackReader { data, pos: 0 };
+    let value = reader.value(0)?;
+
+    if reader.pos != data.len() {
+        return Err(reader.error("trailing bytes after value"));
+    }
+
+    Ok(value)
+}
+
+// Deep enough for any real payload, shallow enough to not overflow the stack
+pub(crate) const MSGPACK_MAX_DEPTH: usize = 128;
+
+struct MsgpackReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MsgpackReader<'a> {
+    fn error(&self, msg: &str) -> CustomError {
+        CustomError::Error(format!("unable to parse msgpack at byte {}: {}", self.pos, msg))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CustomError> {
+        if len > self.data.len() - self.pos {
+            return Err(self.error("unexpected end of data"));
+        }
+
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, len: usize) -> Result<u64, CustomError> {
+        Ok(self.take(len)?.iter().fold(0, |n, b| (n << 8) | *b as u64))
+    }
+
+    fn int(&mut self, len: usize) -> Result<i64, CustomError> {
+        // Sign extend from the top bit of the first byte
+        let shift = 64 - len as u32 * 8;
+        Ok(((self.uint(len)? << shift) as i64) >> shift)
+    }
+
+    fn string(&mut self, len: usize) -> Result<serde_json::Value, CustomError> {
+        let bytes = self.take(len)?;
+
+        str::from_utf8(bytes)
+            .map(|s| serde_json::Value::String(s.to_string()))
+            .map_err(|_| self.error("string is not valid UTF-8"))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<serde_json::Value, CustomError> {
+        use base64::engine::general_purpose::STANDARD;
+
+        Ok(serde_json::Value::String(STANDARD.encode(self.take(len)?)))
+    }
+
+    fn ext(&mut self, len: usize) -> Result<serde_json::Value, CustomError> {
+        self.take(1)?;
+        self.bytes(len)
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<serde_json::Value, CustomError> {
+        // Every element takes at least a byte, so this bounds the allocation
+        let mut values = Vec::with_capacity(len.min(self.data.len() - self.pos));
+
+        for _ in 0..len {
+            values.push(self.value(depth + 1)?);
+        }
+
+        Ok(serde_json::Value::Array(values))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<serde_json::Value, CustomError> {
+        let mut obj = serde_json::Map::new();
+
+        for _ in 0..len {
+            let key = match self.data.get(self.pos) {
+                // bin 8/16/32, with a 1, 2 or 4 byte length
+                Some(&marker @ 0xc4..=0xc6) => {
+                    self.take(1)?;
+                    let len = self.uint(1 << (marker - 0xc4))? as usize;
+
+                    self.take(len)?.iter().map(|b| format!("{:02x}", b)).collect()
+                }
+                _ => match self.value(depth + 1)? {
+                    serde_json::Value::String(key) => key,
+                    key => key.to_string(),
+                },
+            };
+
+            obj.insert(key, self.value(depth + 1)?);
+        }
+
+        Ok(serde_json::Value::Object(obj))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<serde_json::Value, CustomError> {
+        if depth > MSGPACK_MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        let marker = self.take(1)?[0];
+
+        match marker {
+            0x00..=0x7f => Ok(serde_json::Value::from(marker)),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, depth),
+            0x90..=0x9f => self.array((marker & 0x0f) as usize, depth),
+            0xa0..=0xbf => self.string((marker & 0x1f) as usize),
+            0xc0 => Ok(serde_json::Value::Null),
+            0xc1 => Err(self.error("reserved marker 0xc1")),
+            0xc2 => Ok(serde_json::Value::Bool(false)),
+            0xc3 => Ok(serde_json::Value::Bool(true)),
+            0xc4 => {
+                let len = self.uint(1)? as usize;
+                self.bytes(len)
+            }
+            0xc5 => {
+                let len = self.uint(2)? as usize;
+                self.bytes(len)
+            }
+            0xc6 => {
+                let len = self.uint(4)? as usize;
+                self.bytes(len)
+            }
+            0xc7 => {
+                let len = self.uint(1)? as usize;
+                self.ext(len)
+            }
+            0xc8 => {
+                let len = self.uint(2)? as usize;
+                self.ext(len)
+            }
+            0xc9 => {
+                let len = self.uint(4)? as usize;
+                self.ext(len)
+            }
+            // from(f64) maps NaN and infinities to null
+            0xca => Ok(serde_json::Value::from(f32::from_bits(self.uint(4)? as u32) as f64)),
+            0xcb => Ok(serde_json::Value::from(f64::from_bits(self.uint(8)?))),
+            0xcc => Ok(serde_json::Value::from(self.uint(1)?)),
+            0xcd => Ok(serde_json::Value::from(self.uint(2)?)),
+            0xce => Ok(serde_json::Value::from(self.uint(4)?)),
+            0xcf => Ok(serde_json::Value::from(self.uint(8)?)),
+            0xd0 => Ok(serde_json::Value::from(self.int(1)?)),
+            0xd1 => Ok(serde_json::Value::from(self.int(2)?)),
+            0xd2 => Ok(serde_json::Value::from(self.int(4)?)),
+            0xd3 => Ok(serde_json::Value::from(self.int(8)?)),
+            0xd4 => self.ext(1),
+            0xd5 => self.ext(2),
+            0xd6 => self.ext(4),
+            0xd7 => self.ext(8),
+            0xd8 => self.ext(16),
+            0xd9 => {
+                let len = self.uint(1)? as usize;
+                self.string(len)
+            }
+            0xda => {
+                let len = self.uint(2)? as usize;
+                self.string(len)
+            }
+            0xdb => {
+                let len = self.uint(4)? as usize;
+                self.string(len)
+            }
+            0xdc => {
+                let len = self.uint(2)? as usize;
+                self.array(len, depth)
+            }
+            0xdd => {
+                let len = self.uint(4)? as usize;
+                self.array(len, depth)
+            }
+            0xde => {
+                let len = self.uint(2)? as usize;
+                self.map(len, depth)
+            }
+            0xdf => {
+                let len = self.uint(4)? as usize;
+                self.map(len, depth)
+            }
+            0xe0..=0xff => Ok(serde_json::Value::from(marker as i8)),
+        }
+    }
+}
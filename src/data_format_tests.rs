@@ -0,0 +1,738 @@
+use crate::data_format::{sniff, DataFormat};
+use crate::detective::{Detective, Request};
+#[cfg(test)]
+use protos::sp_steps_detective::DetectiveType;
+
+const SAMPLE_YAML: &str = r#"---
+object:
+  field: value
+  number: 42
+"#;
+
+const SAMPLE_YAML_NO_MARKER: &str = r#"object:
+  field: value
+  number: 42
+"#;
+
+const SAMPLE_FORM: &str = "object.field=value&tag=a&tag=b&name=hello%20world";
+
+#[test]
+fn test_sniff() {
+    assert_eq!(sniff(br#"{"a": 1}"#), Some(DataFormat::Json));
+    assert_eq!(sniff(b"  [1, 2]"), Some(DataFormat::Json));
+    assert_eq!(sniff(SAMPLE_YAML.as_bytes()), Some(DataFormat::Yaml));
+    assert_eq!(sniff(SAMPLE_YAML_NO_MARKER.as_bytes()), Some(DataFormat::Yaml));
+    assert_eq!(sniff(SAMPLE_FORM.as_bytes()), Some(DataFormat::Form));
+    assert_eq!(sniff(b"just some text"), None);
+}
+
+#[test]
+fn test_matches_encoded_auto() {
+    let json = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let yaml = SAMPLE_YAML.as_bytes().to_vec();
+    let yaml_no_marker = SAMPLE_YAML_NO_MARKER.as_bytes().to_vec();
+    let form = SAMPLE_FORM.as_bytes().to_vec();
+    let text = "just some text".as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &json,
+                path: "object.field".to_string(),
+                args: vec!["value".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "auto should detect json".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &yaml,
+                path: "object.field".to_string(),
+                args: vec!["value".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "auto should detect yaml with document marker".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: &yaml_no_marker,
+                path: "object.number".to_string(),
+                args: vec!["42".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "auto should detect yaml without document marker".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &form,
+                path: "name".to_string(),
+                args: vec!["hello world".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "auto should detect and decode form data".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &form,
+                path: "tag.1".to_string(),
+                args: vec!["b".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "repeated form keys should become an array".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &text,
+                path: "object.field".to_string(),
+                args: vec!["value".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "undetectable content type should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::Auto);
+}
+
+#[test]
+fn test_matches_encoded_override() {
+    // An explicit format skips sniffing: as YAML this is a bare string, not a map
+    let data = "field=value".as_bytes().to_vec();
+
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        data: &data,
+        path: "field".to_string(),
+        args: vec!["value".to_string()],
+        negate: false,
+    };
+
+    let det = Detective::new();
+
+    assert!(det.matches_encoded(&request, DataFormat::Form).unwrap());
+    assert!(det.matches_encoded(&request, DataFormat::Yaml).is_err());
+}
+
+#[test]
+fn test_matches_encoded_http() {
+    let http_request = "POST /api/v1/users HTTP/1.1\r\nHost: example.com\r\nContent-Type: application/json\r\n\r\n{\"name\": \"test\"}"
+        .as_bytes()
+        .to_vec();
+    let http_response = "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 120\r\nSet-Cookie: session=abc\r\nSet-Cookie: theme=dark\r\n\r\n"
+        .as_bytes()
+        .to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_request,
+                path: "request.method".to_string(),
+                args: vec!["POST".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "request method should equal POST".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_request,
+                path: "request.path".to_string(),
+                args: vec!["/api/v1/users".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "request path should be extracted".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_request,
+                path: "headers.content-type".to_string(),
+                args: vec!["application/json".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "header names should be lowercased".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL,
+                data: &http_response,
+                path: "status.code".to_string(),
+                args: vec!["500".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "status code should be >= 500".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_response,
+                path: "headers.set-cookie.0".to_string(),
+                args: vec!["session=abc".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "first repeated header should be kept".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_response,
+                path: "headers.set-cookie.1".to_string(),
+                args: vec!["theme=dark".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "repeated headers should become an array".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &http_response,
+                path: "request.method".to_string(),
+                args: vec!["POST".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "response should not have a request line".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::Http);
+
+    assert_eq!(sniff(&http_request), Some(DataFormat::Http));
+    assert_eq!(sniff(&http_response), Some(DataFormat::Http));
+    assert_eq!(sniff(b"proto: HTTP/1.1"), Some(DataFormat::Yaml));
+}
+
+#[test]
+fn test_matches_encoded_multipart() {
+    let multipart = concat!(
+        "------WebKitFormBoundary7MA4YWxk\r\n",
+        "Content-Disposition: form-data; name=\"username\"\r\n",
+        "\r\n",
+        "alice\r\n",
+        "------WebKitFormBoundary7MA4YWxk\r\n",
+        "Content-Disposition: form-data; name=\"avatar\"; filename=\"me.png\"\r\n",
+        "Content-Type: image/png\r\n",
+        "\r\n",
+        "\u{89}PNG--not-a-boundary\r\n",
+        "------WebKitFormBoundary7MA4YWxk--\r\n",
+    )
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &multipart,
+                path: "part.avatar.filename".to_string(),
+                args: vec![r"\.(png|jpe?g)$".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "filename should be extracted from content-disposition".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &multipart,
+                path: "part.avatar.content_type".to_string(),
+                args: vec!["image/png".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "part content type should be extracted".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &multipart,
+                path: "part.avatar.content".to_string(),
+                args: vec!["\u{89}PNG--not-a-boundary".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "content should not include the delimiter line break".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &multipart,
+                path: "part.username.content".to_string(),
+                args: vec!["alice".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "plain field content should be extracted".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+                data: &multipart,
+                path: "part.username.filename".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "plain field should not have a filename".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::Multipart);
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::Auto);
+
+    assert_eq!(sniff(&multipart), Some(DataFormat::Multipart));
+}
+
+#[test]
+fn test_matches_encoded_grpc_metadata() {
+    let metadata = concat!(
+        "content-type: application/grpc\n",
+        "User-Agent: grpc-go/1.58.0\n",
+        "x-trace-context-bin: dHJhY2UtaWQ9YWJjLTEyMztvaw==\n",
+        "x-unpadded-bin: aGVsbG8\n",
+        "x-tag: a\n",
+        "x-tag: b\n",
+    )
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &metadata,
+                path: "metadata.content-type".to_string(),
+                args: vec!["application/grpc".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "text metadata value should be extracted as-is".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &metadata,
+                path: "metadata.user-agent".to_string(),
+                args: vec![r"^grpc-go/".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "metadata keys should be lowercased".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY,
+                data: &metadata,
+                path: "metadata.x-trace-context-bin".to_string(),
+                args: vec!["trace-id=abc-123".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "binary metadata should be base64 decoded".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &metadata,
+                path: "metadata.x-unpadded-bin".to_string(),
+                args: vec!["hello".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "unpadded binary metadata should be decoded".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &metadata,
+                path: "metadata.x-tag.1".to_string(),
+                args: vec!["b".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "repeated metadata keys should become an array".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::GrpcMetadata);
+
+    let invalid = "x-trace-context-bin: not base64!\n".as_bytes().to_vec();
+
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+        data: &invalid,
+        path: "metadata.x-trace-context-bin".to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    assert!(Detective::new()
+        .matches_encoded(&request, DataFormat::GrpcMetadata)
+        .is_err());
+}
+
+#[test]
+fn test_matches_encoded_cbor() {
+    use ciborium::Value;
+
+    let value = Value::Map(vec![
+        (
+            Value::Text("device".to_string()),
+            Value::Map(vec![
+                (Value::Text("id".to_string()), Value::Text("sensor-42".to_string())),
+                (Value::Text("temperature".to_string()), Value::Float(21.5)),
+                (Value::Text("firmware".to_string()), Value::Bytes(b"v1.2".to_vec())),
+            ]),
+        ),
+        (
+            Value::Text("readings".to_string()),
+            Value::Array(vec![Value::Integer(3.into()), Value::Integer(7.into())]),
+        ),
+        (Value::Integer(1.into()), Value::Text("integer key".to_string())),
+        (Value::Tag(1, Box::new(Value::Text("when".to_string()))), Value::Integer(1614556800.into())),
+    ]);
+
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&value, &mut cbor).unwrap();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &cbor,
+                path: "device.id".to_string(),
+                args: vec!["sensor-42".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "nested text value should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: &cbor,
+                path: "device.temperature".to_string(),
+                args: vec!["20".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "float value should compare numerically".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &cbor,
+                path: "device.firmware".to_string(),
+                args: vec!["djEuMg==".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "byte strings should become base64".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: &cbor,
+                path: "readings.1".to_string(),
+                args: vec!["7".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "array elements should be addressable".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &cbor,
+                path: "1".to_string(),
+                args: vec!["integer key".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "integer keys should be addressed by their number".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_TIMESTAMP_UNIX,
+                data: &cbor,
+                path: "when".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "tagged keys should use the tagged value".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::Cbor);
+
+    let truncated = cbor[..cbor.len() / 2].to_vec();
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+        data: &truncated,
+        path: "device".to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    assert!(Detective::new().matches_encoded(&request, DataFormat::Cbor).is_err());
+    assert_eq!(sniff(&cbor), None);
+}
+
+#[test]
+fn test_matches_encoded_msgpack() {
+    // {"device": {"id": "sensor-42", "temperature": 21.5, "firmware": bin "v1.2"},
+    //  "readings": [3, -7, 300], 1: "integer key", bin [0xbe, 0xef]: true}
+    let mut msgpack = vec![0x84];
+    msgpack.extend(b"\xa6device\x83");
+    msgpack.extend(b"\xa2id\xa9sensor-42");
+    msgpack.extend(b"\xabtemperature\xcb");
+    msgpack.extend(21.5f64.to_be_bytes());
+    msgpack.extend(b"\xa8firmware\xc4\x04v1.2");
+    msgpack.extend(b"\xa8readings\x93\x03\xf9\xcd\x01\x2c");
+    msgpack.extend(b"\x01\xabinteger key");
+    msgpack.extend(b"\xc4\x02\xbe\xef\xc3");
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &msgpack,
+                path: "device.id".to_string(),
+                args: vec!["sensor-42".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "nested string value should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: &msgpack,
+                path: "device.temperature".to_string(),
+                args: vec!["20".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "float value should compare numerically".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &msgpack,
+                path: "device.firmware".to_string(),
+                args: vec!["djEuMg==".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "bin values should become base64".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN,
+                data: &msgpack,
+                path: "readings.1".to_string(),
+                args: vec!["-5".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "negative fixint should decode".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: &msgpack,
+                path: "readings.2".to_string(),
+                args: vec!["300".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "uint16 should decode".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &msgpack,
+                path: "1".to_string(),
+                args: vec!["integer key".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "integer keys should be addressed by their number".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_BOOLEAN_TRUE,
+                data: &msgpack,
+                path: "beef".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "bin keys should be addressed by their hex".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests_encoded(&test_cases, DataFormat::MessagePack);
+
+    let request = |data| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+        data,
+        path: "device".to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    let truncated = msgpack[..msgpack.len() / 2].to_vec();
+    let err = Detective::new()
+        .matches_encoded(&request(&truncated), DataFormat::MessagePack)
+        .unwrap_err();
+    assert!(err.to_string().contains("unable to parse msgpack"), "{}", err);
+
+    let mut trailing = msgpack.clone();
+    trailing.push(0xc0);
+    assert!(Detective::new()
+        .matches_encoded(&request(&trailing), DataFormat::MessagePack)
+        .is_err());
+
+    let invalid_utf8 = b"\x81\xa1k\xa2\xff\xfe".to_vec();
+    let err = Detective::new()
+        .matches_encoded(&request(&invalid_utf8), DataFormat::MessagePack)
+        .unwrap_err();
+    assert!(err.to_string().contains("not valid UTF-8"), "{}", err);
+
+    // A huge declared length with no data must not allocate or panic
+    let huge = b"\xdd\xff\xff\xff\xff".to_vec();
+    assert!(Detective::new()
+        .matches_encoded(&request(&huge), DataFormat::MessagePack)
+        .is_err());
+
+    assert_eq!(sniff(&msgpack), None);
+}
+
+#[test]
+fn test_msgpack_malformed() {
+    use crate::data_format::{msgpack_to_json, MSGPACK_MAX_DEPTH};
+
+    // One of every sized marker: map 16, array 32, str 8/16/32, bin 8/16/32,
+    // ext 8/16/32, fixext, floats and ints of each width, plus a bin key
+    let mut doc = b"\xde\x00\x03".to_vec();
+    doc.extend(b"\xd9\x01a\xdd\x00\x00\x00\x0d");
+    doc.extend(b"\xda\x00\x01b\xdb\x00\x00\x00\x01c");
+    doc.extend(b"\xc4\x01\x01\xc5\x00\x01\x02\xc6\x00\x00\x00\x01\x03");
+    doc.extend(b"\xc7\x01\x05\x04\xc8\x00\x01\x05\x05\xc9\x00\x00\x00\x01\x05\x06");
+    doc.extend(b"\xd4\x05\x07\xd8\x05");
+    doc.extend([0x08; 16]);
+    doc.extend(b"\xca\x3f\xc0\x00\x00\xcb");
+    doc.extend(1.5f64.to_be_bytes());
+    doc.extend(b"\xd3\xff\xff\xff\xff\xff\xff\xff\xff");
+    doc.extend(b"\xc4\x02\xbe\xef\xcf\x00\x00\x00\x00\x00\x00\x00\x2a");
+    doc.extend(b"\xa1c\x81\xa1d\x92\xc0\xe0");
+
+    let value = msgpack_to_json(&doc).unwrap();
+    assert_eq!(value["a"].as_array().unwrap().len(), 13);
+    assert_eq!(value["beef"], 42);
+    assert_eq!(value["c"]["d"][1], -32);
+
+    // Every truncation of a valid document is an error, never a panic
+    for len in 0..doc.len() {
+        assert!(msgpack_to_json(&doc[..len]).is_err(), "truncated to {} bytes", len);
+    }
+
+    // Deterministic byte flips (xorshift) must not panic either
+    let mut state: u32 = 0x9e37_79b9;
+    for _ in 0..10_000 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+
+        let mut mutated = doc.clone();
+        let pos = state as usize % mutated.len();
+        mutated[pos] = (state >> 24) as u8;
+
+        let _ = msgpack_to_json(&mutated);
+    }
+
+    // Nesting up to the limit is fine, one level more is an error
+    let nested = |depth: usize, open: &[u8]| {
+        let mut data = open.repeat(depth);
+        data.push(0xc0);
+        data
+    };
+
+    assert!(msgpack_to_json(&nested(MSGPACK_MAX_DEPTH, b"\x91")).is_ok());
+    let err = msgpack_to_json(&nested(MSGPACK_MAX_DEPTH + 1, b"\x91")).unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+    assert!(msgpack_to_json(&nested(MSGPACK_MAX_DEPTH + 1, b"\x81\xa1k")).is_err());
+    assert!(msgpack_to_json(&nested(1_000_000, b"\x91")).is_err());
+
+    // Declared lengths far past the end of the data, up to u32::MAX
+    for data in [
+        &b"\xc6\xff\xff\xff\xff\x00"[..],
+        b"\xc9\xff\xff\xff\xff\x01\x00",
+        b"\xc5\xff\xff\x00",
+        b"\xc7\xff\x01",
+        b"\xdb\xff\xff\xff\xffa",
+        b"\xdd\xff\xff\xff\xff\xc0",
+        b"\xdf\xff\xff\xff\xff\xa1k\xc0",
+        b"\x81\xc6\xff\xff\xff\xff\x00\xc0",
+    ] {
+        let err = msgpack_to_json(data).unwrap_err();
+        assert!(err.to_string().contains("unexpected end of data"), "{:02x?}: {}", data, err);
+    }
+}
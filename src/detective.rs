@@ -1,14 +1,92 @@
+use crate::data_format::{self, DataFormat};
 use crate::error::CustomError;
+use crate::expr::{self, Expr};
 use crate::matcher_numeric as numeric;
 use crate::matcher_pii as pii;
-use crate::{matcher_core as core};
+use crate::transform::{self, TRANSFORM_PREFIX};
+use crate::{matcher_core as core, FromValue};
 
+use lazy_static::lazy_static;
+use protobuf::Enum;
 use protos::sp_steps_detective::DetectiveType;
+use regex::Regex;
+use std::collections::HashMap;
 use std::str;
+use std::time::{Duration, Instant};
 
-type MatcherFunc = fn (&Request, gjson::Value) -> Result<bool, CustomError>;
+/// Option arg: when a path isn't found as-is, look for a base64 encoded JSON
+/// string along it and resolve the rest of the path inside the decoded document.
+/// ie. `payload.user.email` where `payload` holds base64 of `{"user": {...}}`.
+/// The option is removed from the args before they reach the matcher.
+pub const TRY_DECODE_BASE64: &str = "try_decode_base64";
 
-pub struct Detective {}
+/// Option arg: read `args[0]` as a path in the same payload rather than a literal,
+/// ie. compare `order.total` against `order.paid`. Both values must have the same
+/// JSON type. Supported by STRING_EQUAL and the single bound NUMERIC_* matchers.
+/// The option is removed from the args before they reach the matcher.
+pub const FIELD_ARG: &str = "field_arg";
+
+/// Option arg prefix for `[start:end]` slice paths (see `PathSlice`): how the
+/// results for each element combine. `slice:any` (the default) matches when any
+/// element matches, `slice:all` when every element does and `slice:count:<op>:<n>`
+/// when the number of matching elements compares to n, with op as in `compare()`
+/// (ie. `slice:count:gte:2`). An empty slice matches only a count of 0.
+/// The option is removed from the args before they reach the matcher.
+pub const SLICE_MODE_PREFIX: &str = "slice:";
+
+pub type MatcherFunc = fn (&Request, gjson::Value) -> Result<bool, CustomError>;
+
+// A matcher that may capture state, ie. a pattern override
+type MatcherRef<'a> = dyn Fn(&Request, gjson::Value) -> Result<bool, CustomError> + 'a;
+
+/// `Send + Sync`, so one detective can be shared between threads behind an `Arc`.
+/// Any caches must stay behind a `Mutex` (see `matcher_core::cached_regex()`).
+pub struct Detective {
+    strict: bool,
+    overrides: pii::PatternOverrides,
+}
+
+lazy_static! {
+    static ref PATH_SLICE_RE: Regex = Regex::new(r"^(.+?)\[(\d*):(\d*)\](?:\.(.+))?$").unwrap();
+}
+
+/// A `[start:end]` slice qualifier on a path, e.g. `items[0:3]` or `items[0:3].price`.
+/// `end` is exclusive and either bound may be omitted.
+#[derive(Debug, PartialEq)]
+pub struct PathSlice<'a> {
+    pub base: &'a str,
+    pub start: usize,
+    pub end: Option<usize>,
+    pub rest: Option<&'a str>,
+}
+
+/// How the element results of a slice path combine, see `SLICE_MODE_PREFIX`.
+#[derive(Debug, PartialEq)]
+pub enum SliceMode {
+    Any,
+    All,
+    Count { op: String, count: usize },
+}
+
+/// A payload that has been validated as UTF-8 once so that many rules can be
+/// evaluated against it with `Detective::matches_document()`.
+pub struct Document<'a> {
+    data: &'a Vec<u8>,
+    json: &'a str,
+}
+
+impl<'a> Document<'a> {
+    pub fn parse(data: &'a Vec<u8>) -> Result<Self, CustomError> {
+        if data.is_empty() {
+            return Err(CustomError::Error("data cannot be empty".to_string()));
+        }
+
+        let json = str::from_utf8(data)
+            .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+        Ok(Document { data, json })
+    }
+}
 
 #[derive(Clone)]
 pub struct Request<'a> {
@@ -19,6 +97,226 @@ pub struct Request<'a> {
     pub negate: bool,
 }
 
+// Matchers without a DetectiveType of their own, addressable by name through
+// Detective::matches_named(). Numeric and PII matchers carry the same prefix
+// their DetectiveType siblings do, ie. "pii_iban".
+const NAMED_MATCHERS: &[(&str, MatcherFunc)] = &[
+    ("distinct_chars", core::distinct_chars),
+    ("any_format", core::any_format),
+    ("is_base32", core::is_base32),
+    ("sequential_keys", core::sequential_keys),
+    ("isbn", core::isbn),
+    ("issn", core::issn),
+    ("glob", core::glob),
+    ("whitespace_anomaly", core::whitespace_anomaly),
+    ("mojibake", core::mojibake),
+    ("data_uri", core::data_uri),
+    ("pii_cloud_key", pii::cloud_key),
+    ("numeric_sign", numeric::sign),
+    ("emoji_ratio", core::emoji_ratio),
+    ("array_unique", core::array_unique),
+    ("barcode", core::barcode),
+    ("log_level", core::log_level),
+    ("duration", core::duration),
+    ("segment", core::segment),
+    ("mixed_script", core::mixed_script),
+    ("is_regex", core::is_regex),
+    ("hex_color", core::hex_color),
+    ("timestamp_close", core::timestamp_close),
+    ("is_base64", core::is_base64),
+    ("numeric_within_percent", numeric::within_percent),
+    ("is_normalized", core::is_normalized),
+    ("string_starts_with", core::string_starts_with),
+    ("string_ends_with", core::string_ends_with),
+    ("truncated_utf8", core::truncated_utf8),
+    ("template", core::template),
+    ("pii_national_id", pii::national_id),
+    ("injection", core::injection),
+    ("ip_cidr", core::ip_cidr),
+    ("semver_compare", core::semver_compare),
+    ("pii_geolocation_precise", pii::geolocation_precise),
+    ("string_length_equal", core::string_length_equal),
+    ("string_length_greater_than", core::string_length_greater_than),
+    ("string_length_less_than", core::string_length_less_than),
+    ("timestamp_after", core::timestamp_after),
+    ("timestamp_before", core::timestamp_before),
+    ("timestamp_between", core::timestamp_between),
+    ("timestamp_monotonic", core::timestamp_monotonic),
+    ("pii_iban", pii::iban),
+    ("pii_crypto_address", pii::crypto_address),
+    ("pii_uk_bank", pii::uk_bank),
+    ("numeric_expr", numeric::expr),
+    ("pii_high_entropy_substring", pii::high_entropy_substring),
+    ("is_null", core::is_null),
+    ("array_sorted", core::array_sorted),
+    ("numeric_between", numeric::between),
+    ("license_plate", core::license_plate),
+    ("control_chars", core::control_chars),
+    ("git_sha", core::git_sha),
+];
+
+/// The matcher registered under `name` (see `Detective::supported_matcher_names()`),
+/// with its name as registered.
+pub(crate) fn named_matcher(name: &str) -> Option<(&'static str, MatcherFunc)> {
+    NAMED_MATCHERS.iter().find(|(n, _)| *n == name).copied()
+}
+
+/// JSON type of a resolved value, using the same names `DETECTIVE_TYPE_IS_TYPE` accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+    Null,
+}
+
+impl JsonType {
+    /// `None` when the value doesn't exist in the payload.
+    pub fn of(value: &gjson::Value) -> Option<JsonType> {
+        if !value.exists() {
+            return None;
+        }
+
+        Some(match value.kind() {
+            gjson::Kind::String => JsonType::String,
+            gjson::Kind::Number => JsonType::Number,
+            gjson::Kind::True | gjson::Kind::False => JsonType::Bool,
+            gjson::Kind::Array => JsonType::Array,
+            gjson::Kind::Object => JsonType::Object,
+            gjson::Kind::Null => JsonType::Null,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsonType::String => "string",
+            JsonType::Number => "number",
+            JsonType::Bool => "bool",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+            JsonType::Null => "null",
+        }
+    }
+}
+
+/// Outcome of `Detective::matches_detailed()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchResult {
+    pub matched: bool,
+    /// Type of the value at `request.path` before any matcher coercion. `None`
+    /// for payload-wide requests and for paths that don't exist (ie. `has_field`).
+    pub value_type: Option<JsonType>,
+    /// A value found more than once, as raw JSON (see `matches_array_unique()`).
+    pub duplicate: Option<String>,
+    /// Whether the path was resolved inside a base64 encoded JSON string
+    /// (see `TRY_DECODE_BASE64`).
+    pub decoded: bool,
+    /// For multi value paths (ie. `items.#.price`), the outcome of the matcher
+    /// on each value, before `request.negate` is applied. Each path resolves
+    /// to that value on its own, ie. `items.#.price|1`. Empty for other paths.
+    pub elements: Vec<(String, bool)>,
+    /// Args found in the value by the string equal/contains matchers (see
+    /// `matcher_core::matched_args()`). Reported regardless of `request.negate`.
+    pub matched_args: Vec<String>,
+    /// Index of the first out of order element (see `matches_array_sorted()`).
+    pub unsorted_index: Option<usize>,
+    /// The value the matcher ran against, unquoted for strings and as raw JSON
+    /// otherwise. `None` when there is no single value, ie. for payload-wide,
+    /// slice and multi value paths, or a path that doesn't exist.
+    pub matched_value: Option<String>,
+    /// For PII match types, the category found: the detector that hit for
+    /// `DETECTIVE_TYPE_PII_ANY`, otherwise the match type itself when it matched.
+    /// Reported regardless of `request.negate`.
+    pub detected_type: Option<DetectiveType>,
+    /// When a credit card number was detected, its brand from the issuer prefix
+    /// and length, ie. "visa" (see `matcher_pii::card_brand()`).
+    pub card_brand: Option<&'static str>,
+    /// For `DETECTIVE_TYPE_MAC_ADDRESS` hits, the address in canonical form
+    /// (see `matcher_core::normalize_mac()`).
+    pub normalized_value: Option<String>,
+}
+
+/// Outcome of `Detective::matches_many_within()`.
+#[derive(Debug)]
+pub struct BatchResult {
+    /// One result per evaluated request, in request order.
+    pub results: Vec<Result<bool, CustomError>>,
+    /// False when the budget ran out before every request was evaluated.
+    pub complete: bool,
+}
+
+// Run `f` over `requests` in order until they're done or `budget` has elapsed
+pub(crate) fn evaluate_within<F>(requests: &[Request], budget: Duration, f: F) -> BatchResult
+where
+    F: Fn(&Request) -> Result<bool, CustomError>,
+{
+    let deadline = Instant::now() + budget;
+    let mut results = Vec::with_capacity(requests.len());
+
+    for request in requests {
+        if Instant::now() >= deadline {
+            return BatchResult {
+                results,
+                complete: false,
+            };
+        }
+
+        results.push(f(request));
+    }
+
+    BatchResult {
+        results,
+        complete: true,
+    }
+}
+
+/// Rules with a label each, for classifying payloads (see `RuleSet::tags()`).
+/// Several rules may share a label.
+pub struct RuleSet {
+    detective: Detective,
+    rules: Vec<(String, DetectiveType, String, Vec<String>)>,
+}
+
+impl RuleSet {
+    /// `rules` are `(label, match_type, path, args)`.
+    pub fn new(rules: Vec<(String, DetectiveType, String, Vec<String>)>) -> Self {
+        RuleSet {
+            detective: Detective::new(),
+            rules,
+        }
+    }
+
+    /// Labels of the rules that match `data`, in rule order and each listed once,
+    /// ie. `["contains_pii", "high_value_txn"]`. Rules are evaluated with
+    /// `Detective::matches_lenient()`, so a rule on a path the payload doesn't
+    /// have simply doesn't tag it. Any other error is returned.
+    pub fn tags(&self, data: &Vec<u8>) -> Result<Vec<String>, CustomError> {
+        let mut tags: Vec<String> = Vec::new();
+
+        for (label, match_type, path, args) in &self.rules {
+            if tags.contains(label) {
+                continue;
+            }
+
+            let request = Request {
+                match_type: *match_type,
+                data,
+                path: path.clone(),
+                args: args.clone(),
+                negate: false,
+            };
+
+            if self.detective.matches_lenient(&request)? {
+                tags.push(label.clone());
+            }
+        }
+
+        Ok(tags)
+    }
+}
+
 impl Default for Detective {
     fn default() -> Self {
         Detective::new()
@@ -28,32 +326,504 @@ impl Default for Detective {
 impl Detective {
     pub fn new() -> Self {
         // env_logger::init();
-        Detective {}
+        Detective {
+            strict: false,
+            overrides: pii::PatternOverrides::new(),
+        }
+    }
+
+    /// A detective that rejects requests with args the matcher doesn't
+    /// understand, ie. a misspelled option like `ignore_test_value`, instead
+    /// of ignoring them. See `validate_options()`.
+    pub fn new_strict() -> Self {
+        Detective {
+            strict: true,
+            ..Detective::new()
+        }
     }
+
+    /// Match the given PII detectors (ie. `DETECTIVE_TYPE_PII_EMAIL`) on a regex
+    /// instead of their built-in checks, including when run as part of
+    /// `DETECTIVE_TYPE_PII_ANY` or `pii_summary()`. Options the built-in check
+    /// takes, like `ignore_test_values`, don't apply to an override.
+    ///
+    /// Only the detectors `DETECTIVE_TYPE_PII_ANY` consults can be overridden:
+    /// credit card, SSN and email. Any other type is an `InvalidArgument` error.
+    ///
+    /// Replaces any earlier overrides. Every pattern is compiled up front, and
+    /// on error none of them are applied.
+    pub fn set_pattern_overrides(
+        &mut self,
+        overrides: HashMap<DetectiveType, String>,
+    ) -> Result<(), CustomError> {
+        let mut compiled = pii::PatternOverrides::new();
+
+        for (match_type, pattern) in overrides {
+            if !pii::PII_DETECTORS.iter().any(|(t, _)| *t == match_type) {
+                return Err(CustomError::InvalidArgument(format!(
+                    "{:?} can't be overridden: must be one of {}",
+                    match_type,
+                    pii::PII_DETECTORS
+                        .iter()
+                        .map(|(t, _)| format!("{:?}", t))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )));
+            }
+
+            compiled.insert(match_type, Regex::new(&pattern)?);
+        }
+
+        self.overrides = compiled;
+
+        Ok(())
+    }
+
+    // The request's matcher, honoring any pattern override for its match type
+    fn matcher(
+        &self,
+        request: &Request,
+    ) -> Result<impl Fn(&Request, gjson::Value) -> Result<bool, CustomError> + '_, CustomError> {
+        let f = Detective::get_matcher_func(request)?;
+        let pattern = self.overrides.get(&request.match_type);
+
+        Ok(move |request: &Request, field: gjson::Value| match pattern {
+            Some(re) => Ok(re.is_match(field.str())),
+            None if request.match_type == DetectiveType::DETECTIVE_TYPE_PII_ANY => {
+                Ok(pii::any_detail_with(request, field, &self.overrides)?.is_some())
+            }
+            None => f(request, field),
+        })
+    }
+
+    /// Run the request's matcher, inverting the result when `request.negate` is
+    /// set. Errors are never inverted.
     pub fn matches(&self, request: &Request) -> Result<bool, CustomError> {
-        validate_request(request)?;
+        Ok(self.matches_detailed(request)?.matched)
+    }
+
+    /// Like `matches()`, but a path missing from the payload doesn't match instead
+    /// of erroring, so rules on optional fields can be OR-ed together. The result
+    /// is false for a missing path whether or not `request.negate` is set.
+    pub fn matches_lenient(&self, request: &Request) -> Result<bool, CustomError> {
+        match self.matches(request) {
+            Err(CustomError::PathNotFound(_)) => Ok(false),
+            result => result,
+        }
+    }
+
+    /// Match each element in a sliced array, combining the results as the
+    /// `SLICE_MODE_PREFIX` option says (any element by default).
+    ///
+    /// Bounds past the end of the array are clamped; a slice that ends up empty
+    /// doesn't match unless the mode is a count of 0.
+    fn matches_slice(
+        &self,
+        request: &Request,
+        data_as_str: &str,
+        slice: &PathSlice,
+    ) -> Result<bool, CustomError> {
+        if request.match_type == DetectiveType::DETECTIVE_TYPE_HAS_FIELD {
+            return Err(CustomError::Error(
+                "has_field does not support slice paths".to_string(),
+            ));
+        }
+
+        let (mode, args) = parse_slice_mode(&request.args)?;
+        let request = &Request {
+            args,
+            ..request.clone()
+        };
+
+        let array = gjson::get(data_as_str, slice.base);
+
+        if array.kind() != gjson::Kind::Array {
+            return Err(CustomError::Error(format!(
+                "path '{}' is not an array",
+                slice.base
+            )));
+        }
+
+        let elements = array.array();
+        let end = slice.end.unwrap_or(elements.len()).min(elements.len());
+        let start = slice.start.min(end);
+
+        let f = self.matcher(request)?;
+        let mut matched = 0;
+
+        for element in &elements[start..end] {
+            let field = match slice.rest {
+                Some(rest) => element.get(rest),
+                None => gjson::parse(element.json()),
+            };
+
+            match (f(request, field)?, &mode) {
+                (true, SliceMode::Any) => return Ok(true),
+                (false, SliceMode::All) => return Ok(false),
+                (true, _) => matched += 1,
+                (false, _) => {}
+            }
+        }
+
+        match mode {
+            SliceMode::Any => Ok(false),
+            SliceMode::All => Ok(start < end),
+            SliceMode::Count { op, count } => compare(&op, matched, count),
+        }
+    }
+
+    /// Parse a rule expression (see `expr::parse()`) and evaluate it against `data`.
+    pub fn matches_expr(&self, expr: &str, data: &[u8]) -> Result<bool, CustomError> {
+        let tree = expr::parse(expr)?;
+
+        self.matches_tree(&tree, &data.to_vec())
+    }
+
+    /// Evaluate a tree of match requests against `data`. `&&` and `||` short-circuit.
+    ///
+    /// Errors from a leaf are wrapped in `CustomError::InTree` naming the leaf
+    /// and how it was reached.
+    pub fn matches_tree(&self, tree: &Expr, data: &Vec<u8>) -> Result<bool, CustomError> {
+        self.matches_subtree(tree, data, &mut Vec::new())
+    }
+
+    fn matches_subtree(
+        &self,
+        tree: &Expr,
+        data: &Vec<u8>,
+        breadcrumb: &mut Vec<&str>,
+    ) -> Result<bool, CustomError> {
+        let mut branch = |crumb: &'static str, subtree: &Expr| {
+            breadcrumb.push(crumb);
+            let result = self.matches_subtree(subtree, data, breadcrumb);
+            breadcrumb.pop();
+            result
+        };
+
+        let (result, name, path) = match tree {
+            Expr::And(left, right) => return Ok(branch("and[0]", left)? && branch("and[1]", right)?),
+            Expr::Or(left, right) => return Ok(branch("or[0]", left)? || branch("or[1]", right)?),
+            Expr::Not(inner) => return Ok(!branch("not", inner)?),
+            Expr::Match {
+                match_type,
+                path,
+                args,
+            } => {
+                let result = self.matches(&Request {
+                    match_type: *match_type,
+                    data,
+                    path: path.clone(),
+                    args: args.clone(),
+                    negate: false,
+                });
+                let name = format!("{:?}", match_type).to_ascii_lowercase();
+
+                (result, name.trim_start_matches("detective_type_").to_string(), path)
+            }
+            Expr::Named { name, path, args } => {
+                let result = self.matches_named(
+                    name,
+                    &Request {
+                        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                        data,
+                        path: path.clone(),
+                        args: args.clone(),
+                        negate: false,
+                    },
+                );
+
+                (result, name.clone(), path)
+            }
+        };
+
+        result.map_err(|err| {
+            let leaf = format!("{} on '{}'", name, path);
+
+            let mut crumbs = breadcrumb.clone();
+            crumbs.push(&leaf);
+
+            CustomError::InTree {
+                breadcrumb: crumbs.join(" > "),
+                source: Box::new(err),
+            }
+        })
+    }
+
+    /// Walk every leaf (string, number, bool) in `data` and return the paths of
+    /// those the matcher returns true for. Paths use the same syntax `matches()`
+    /// accepts, ie. `object.emails.1`. Leaves the matcher errors on are skipped.
+    pub fn find_matching_paths(
+        &self,
+        data: &Vec<u8>,
+        match_type: DetectiveType,
+        args: Vec<String>,
+    ) -> Result<Vec<String>, CustomError> {
+        let request = Request {
+            match_type,
+            data,
+            path: String::new(),
+            args,
+            negate: false,
+        };
+
+        self.validate_request(&request)?;
+
+        let data_as_str = str::from_utf8(data)
+            .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+        let f = self.matcher(&request)?;
+        let mut paths = Vec::new();
+
+        collect_matching_paths(&request, gjson::parse(data_as_str), "", &f, &mut paths);
+
+        Ok(paths)
+    }
+
+    /// Count the leaves (strings, numbers, bools) in `data` each PII detector
+    /// flags. Detectors without any hits are left out. A leaf can count towards
+    /// more than one type.
+    pub fn pii_summary(&self, data: &Vec<u8>) -> Result<HashMap<DetectiveType, usize>, CustomError> {
+        if data.is_empty() {
+            return Err(CustomError::Error("data cannot be empty".to_string()));
+        }
+
+        let data_as_str = str::from_utf8(data)
+            .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+        let mut summary = HashMap::new();
+
+        count_pii(data, gjson::parse(data_as_str), &self.overrides, &mut summary);
+
+        Ok(summary)
+    }
+
+    /// Explain, per PII detector, why the value at `request.path` was or wasn't
+    /// flagged. Useful for tuning when expected PII isn't detected. Detectors with
+    /// a pattern override (see `set_pattern_overrides()`) report on that pattern.
+    /// If the value can't be resolved, every detector reports the reason as a miss.
+    pub fn diagnose_pii(&self, request: &Request) -> Vec<pii::PiiDiagnosis> {
+        let field = if request.path.is_empty() {
+            Err(CustomError::Error("diagnose_pii requires a path".to_string()))
+        } else {
+            parse_field(request.data, &request.path)
+        };
+
+        match field {
+            Ok(field) => pii::diagnose_with(request, &field, &self.overrides),
+            Err(err) => pii::PII_DETECTORS
+                .iter()
+                .map(|(match_type, _)| pii::PiiDiagnosis {
+                    match_type: *match_type,
+                    matched: false,
+                    reason: err.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Like `matches()`, but `${name}` references in `request.args` are first
+    /// replaced with values from `vars` (see `substitute_vars()`).
+    pub fn matches_with_vars(
+        &self,
+        request: &Request,
+        vars: &HashMap<String, String>,
+    ) -> Result<bool, CustomError> {
+        let request = Request {
+            args: substitute_vars(&request.args, vars)?,
+            ..request.clone()
+        };
+
+        self.matches(&request)
+    }
+
+    /// Like `matches()`, but `request.data` is encoded as `format` rather than JSON.
+    /// The payload is transcoded to JSON once and then matched as usual.
+    pub fn matches_encoded(
+        &self,
+        request: &Request,
+        format: DataFormat,
+    ) -> Result<bool, CustomError> {
+        let json = data_format::to_json(request.data, format)?.into_bytes();
 
-        if !request.path.is_empty() {
-            // Matching on path value
-            self.matches_path(request)
+        let json_request = Request {
+            data: &json,
+            ..request.clone()
+        };
+
+        self.matches(&json_request)
+    }
+
+    /// Like `matches()`, but also reports details about the resolved value.
+    pub fn matches_detailed(&self, request: &Request) -> Result<MatchResult, CustomError> {
+        self.validate_request(request)?;
+
+        let data_as_str = str::from_utf8(request.data)
+            .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+        let result = if request.path.is_empty() {
+            MatchResult {
+                matched: self.matches_payload_str(request, data_as_str)?,
+                ..Default::default()
+            }
         } else {
-            // Matching on any field in the payload
-            self.matches_payload(request)
+            self.matches_path_detailed(request, data_as_str)?
+        };
+
+        Ok(MatchResult {
+            matched: result.matched != request.negate,
+            ..result
+        })
+    }
+
+    /// Assert that the array at `request.path` has no repeated values, reporting
+    /// the first duplicate found. `request.args` may hold a path within each
+    /// element to compare (see `matcher_core::array_unique()`).
+    ///
+    /// `request.match_type` is ignored as there is no detective type for this yet.
+    pub fn matches_array_unique(&self, request: &Request) -> Result<MatchResult, CustomError> {
+        if request.args.len() > 1 {
+            return Err(CustomError::Error(
+                "array_unique accepts at most 1 argument".to_string(),
+            ));
+        }
+
+        let field = parse_field(request.data, &request.path)?;
+        let value_type = JsonType::of(&field);
+        let duplicate = core::find_duplicate(&field, request.args.first().map(String::as_str))?;
+
+        Ok(MatchResult {
+            matched: duplicate.is_none(),
+            value_type,
+            duplicate,
+            ..Default::default()
+        })
+    }
+
+    /// Assert that the array at `request.path` is sorted in the direction given
+    /// as the only arg, reporting the first element out of order (see
+    /// `matcher_core::find_unsorted()`).
+    ///
+    /// `request.match_type` is ignored as there is no detective type for this yet.
+    pub fn matches_array_sorted(&self, request: &Request) -> Result<MatchResult, CustomError> {
+        if request.args.len() != 1 {
+            return Err(CustomError::Error(
+                "array_sorted requires exactly 1 argument".to_string(),
+            ));
+        }
+
+        let field = parse_field(request.data, &request.path)?;
+        let value_type = JsonType::of(&field);
+        let unsorted_index = core::find_unsorted(&field, &request.args[0])?;
+
+        Ok(MatchResult {
+            matched: unsorted_index.is_none(),
+            value_type,
+            unsorted_index,
+            ..Default::default()
+        })
+    }
+
+    /// Evaluate many `(match_type, path, args)` rules against an already parsed
+    /// document. This is the equivalent of calling `matches()` once per rule with
+    /// the same data, minus the repeated UTF-8 validation of the payload.
+    ///
+    /// Results are returned in the same order as `rules`; an error in one rule
+    /// doesn't stop the others from being evaluated.
+    pub fn matches_document(
+        &self,
+        doc: &Document,
+        rules: &[(DetectiveType, String, Vec<String>)],
+    ) -> Vec<Result<bool, CustomError>> {
+        rules
+            .iter()
+            .map(|(match_type, path, args)| {
+                let request = Request {
+                    match_type: *match_type,
+                    data: doc.data,
+                    path: path.clone(),
+                    args: args.clone(),
+                    negate: false,
+                };
+
+                self.validate_request(&request)?;
+
+                if request.path.is_empty() {
+                    self.matches_payload_str(&request, doc.json)
+                } else {
+                    self.matches_path_str(&request, doc.json)
+                }
+            })
+            .collect()
+    }
+
+    /// Evaluate many requests, validating the payload as UTF-8 only once.
+    ///
+    /// Requests are expected to share the same `data`, as when applying a rule
+    /// set to one message; a request whose data differs from the first one's is
+    /// still evaluated correctly but parsed on its own. Results are returned in
+    /// the same order as `requests`; an error in one request doesn't stop the
+    /// others from being evaluated.
+    pub fn matches_many(&self, requests: &[Request]) -> Vec<Result<bool, CustomError>> {
+        let doc = requests.first().and_then(|r| Document::parse(r.data).ok());
+
+        requests
+            .iter()
+            .map(|request| self.matches_in_document(request, doc.as_ref()))
+            .collect()
+    }
+
+    /// Like `matches_many()`, but stops once `budget` has elapsed. The deadline
+    /// is checked before each request, so a request already being evaluated runs
+    /// to completion. `BatchResult::results` then holds only the requests that
+    /// were evaluated, in order, and `complete` is false.
+    pub fn matches_many_within(&self, requests: &[Request], budget: Duration) -> BatchResult {
+        let doc = requests.first().and_then(|r| Document::parse(r.data).ok());
+
+        evaluate_within(requests, budget, |request| {
+            self.matches_in_document(request, doc.as_ref())
+        })
+    }
+
+    // matches() for a request whose payload may already be parsed as `doc`
+    fn matches_in_document(
+        &self,
+        request: &Request,
+        doc: Option<&Document>,
+    ) -> Result<bool, CustomError> {
+        match doc {
+            Some(doc) if std::ptr::eq(request.data, doc.data) || request.data == doc.data => {
+                self.validate_request(request)?;
+
+                let matched = if request.path.is_empty() {
+                    self.matches_payload_str(request, doc.json)?
+                } else {
+                    self.matches_path_str(request, doc.json)?
+                };
+
+                Ok(matched != request.negate)
+            }
+            _ => self.matches(request),
         }
     }
 
+    /// Match on any field in the payload. Unlike `matches()`, `request.negate`
+    /// is not applied.
     pub fn matches_payload(&self, request: &Request) -> Result<bool, CustomError> {
         let data_as_str = str::from_utf8(request.data)
             .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
 
+        self.matches_payload_str(request, data_as_str)
+    }
+
+    fn matches_payload_str(&self, request: &Request, data_as_str: &str) -> Result<bool, CustomError> {
         let obj = gjson::parse(data_as_str);
 
         let mut found: bool = false;
 
-        let f = Detective::get_matcher_func(request)?;
+        let f = self.matcher(request)?;
 
         obj.each(|_, value| {
-            let res = recurse_field(request, value, f);
+            let res = recurse_field(request, value, &f);
             if res {
                 found = true;
             }
@@ -67,21 +837,377 @@ impl Detective {
         }
     }
 
+    /// Match on the value at `request.path`. Unlike `matches()`, `request.negate`
+    /// is not applied.
     pub fn matches_path(&self, request: &Request) -> Result<bool, CustomError> {
-        // parse_field() will return an error if the path is not found
+        let data_as_str = str::from_utf8(request.data)
+            .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+        self.matches_path_str(request, data_as_str)
+    }
+
+    fn matches_path_str(&self, request: &Request, data_as_str: &str) -> Result<bool, CustomError> {
+        Ok(self.matches_path_detailed(request, data_as_str)?.matched)
+    }
+
+    fn matches_path_detailed(
+        &self,
+        request: &Request,
+        data_as_str: &str,
+    ) -> Result<MatchResult, CustomError> {
+        if request.args.iter().any(|a| a == FIELD_ARG) {
+            let request = resolve_field_arg(request, data_as_str)?;
+            return self.matches_path_detailed(&request, data_as_str);
+        }
+
+        if request.args.iter().any(|a| a == TRY_DECODE_BASE64) {
+            let request = Request {
+                args: request
+                    .args
+                    .iter()
+                    .filter(|a| a.as_str() != TRY_DECODE_BASE64)
+                    .cloned()
+                    .collect(),
+                ..request.clone()
+            };
+
+            let (json, path) = match decode_base64_path(data_as_str, &request.path) {
+                Some(decoded) => decoded,
+                None => return self.matches_path_detailed(&request, data_as_str),
+            };
+
+            let data = json.into_bytes();
+            let decoded_request = Request {
+                data: &data,
+                path,
+                ..request
+            };
+
+            let data_as_str = str::from_utf8(&data).map_err(|e| {
+                CustomError::Error(format!("unable to convert bytes to string: {}", e))
+            })?;
+
+            return Ok(MatchResult {
+                decoded: true,
+                ..self.matches_path_detailed(&decoded_request, data_as_str)?
+            });
+        }
+
+        if request.args.iter().any(|a| a.starts_with(TRANSFORM_PREFIX)) {
+            return self.matches_path_transformed(request, data_as_str);
+        }
+
+        if let Some(slice) = parse_path_slice(&request.path)? {
+            return Ok(MatchResult {
+                matched: self.matches_slice(request, data_as_str, &slice)?,
+                value_type: Some(JsonType::Array),
+                ..Default::default()
+            });
+        }
+
+        if request.args.iter().any(|a| a.starts_with(SLICE_MODE_PREFIX)) {
+            return Err(CustomError::Error(format!(
+                "'{}' options require a slice path",
+                SLICE_MODE_PREFIX
+            )));
+        }
+
+        // get_field() will return an error if the path is not found
         // but for this single check, we don't want to error out
-        let field: gjson::Value = if request.match_type == DetectiveType::DETECTIVE_TYPE_HAS_FIELD {
-            gjson::Value::default()
+        let (field, value_type) = if request.match_type == DetectiveType::DETECTIVE_TYPE_HAS_FIELD {
+            let value_type = JsonType::of(&gjson::get(data_as_str, &request.path));
+            (gjson::Value::default(), value_type)
+        } else if request.match_type == DetectiveType::DETECTIVE_TYPE_IS_EMPTY {
+            // is_empty() decides what a missing path means, see MISSING_IS_EMPTY
+            let field = gjson::get(data_as_str, &request.path);
+            let value_type = JsonType::of(&field);
+            (field, value_type)
+        } else if is_multi_value_path(&request.path) && matches_any_value(request.match_type) {
+            let f = self.matcher(request)?;
+
+            // Every value is evaluated so that elements is complete
+            let elements = get_field_values(data_as_str, &request.path)?
+                .iter()
+                .enumerate()
+                .map(|(i, json)| {
+                    let matched = f(request, gjson::parse(json))?;
+                    Ok((format!("{}|{}", request.path, i), matched))
+                })
+                .collect::<Result<Vec<(String, bool)>, CustomError>>()?;
+
+            return Ok(MatchResult {
+                matched: elements.iter().any(|(_, matched)| *matched),
+                value_type: Some(JsonType::Array),
+                elements,
+                ..Default::default()
+            });
         } else {
-            parse_field(request.data, &request.path)?
+            let field = get_field(data_as_str, &request.path)?;
+            let value_type = JsonType::of(&field);
+            (field, value_type)
         };
 
-        let f = Detective::get_matcher_func(request)?;
+        Ok(MatchResult {
+            value_type,
+            ..self.run_matcher(request, field)?
+        })
+    }
 
-        f(request, field)
+    /// Names of the matchers that have no `DetectiveType`, for use with
+    /// `matches_named()`.
+    pub fn supported_matcher_names() -> Vec<&'static str> {
+        NAMED_MATCHERS.iter().map(|(name, _)| *name).collect()
     }
 
-    fn get_matcher_func(request: &Request) -> Result<MatcherFunc, CustomError> {
+    /// Run the matcher registered as `name` (see `supported_matcher_names()`) on
+    /// the value at `request.path`, inverting the result when `request.negate` is
+    /// set. `request.match_type` is ignored.
+    pub fn matches_named(&self, name: &str, request: &Request) -> Result<bool, CustomError> {
+        let (_, f) = named_matcher(name)
+            .ok_or_else(|| CustomError::MatchError(format!("unknown matcher: {}", name)))?;
+
+        if request.data.is_empty() {
+            return Err(CustomError::Error("data cannot be empty".to_string()));
+        }
+
+        let field = parse_field(request.data, &request.path)?;
+
+        Ok(f(request, field)? != request.negate)
+    }
+
+    // Run the transforms named in the args (see TRANSFORM_PREFIX) over the value
+    // at the path, then the matcher over the result as a JSON string
+    fn matches_path_transformed(
+        &self,
+        request: &Request,
+        data_as_str: &str,
+    ) -> Result<MatchResult, CustomError> {
+        let (transforms, args) = transform::split_args(&request.args)?;
+
+        if parse_path_slice(&request.path)?.is_some() {
+            return Err(CustomError::Error(
+                "transforms do not support slice paths".to_string(),
+            ));
+        }
+
+        let request = Request {
+            args,
+            ..request.clone()
+        };
+
+        let field = get_field(data_as_str, &request.path)?;
+        let value = transform::apply_all(&transforms, field.str())?;
+        let json = serde_json::Value::String(value).to_string();
+
+        Ok(MatchResult {
+            value_type: JsonType::of(&field),
+            ..self.run_matcher(&request, gjson::parse(&json))?
+        })
+    }
+
+    /// Run a matcher against an already resolved value, skipping the
+    /// `str::from_utf8()` + `gjson::get()` round trip done by `matches()`.
+    ///
+    /// There is no payload, so `DETECTIVE_TYPE_HAS_FIELD` always errors here.
+    pub fn matches_value(
+        &self,
+        match_type: DetectiveType,
+        args: Vec<String>,
+        negate: bool,
+        value: gjson::Value,
+    ) -> Result<bool, CustomError> {
+        if match_type == DetectiveType::DETECTIVE_TYPE_HAS_FIELD {
+            return Err(CustomError::Error(
+                "has_field requires a payload, use matches() instead".to_string(),
+            ));
+        }
+
+        let data = Vec::new();
+
+        let request = Request {
+            match_type,
+            data: &data,
+            path: String::new(),
+            args,
+            negate,
+        };
+
+        let f = self.matcher(&request)?;
+
+        Ok(f(&request, value)? != negate)
+    }
+
+    /// Like `matches()`, but against an already parsed `value` instead of
+    /// `request.data`, which is ignored. Dotted paths of plain keys and array
+    /// indexes (ie. `object.items.0.name`) are resolved on `value` directly; any
+    /// other path, payload-wide requests and requests with option args serialize
+    /// `value` once and go through `matches()`, so results are the same either way.
+    pub fn matches_json_value(
+        &self,
+        request: &Request,
+        value: &serde_json::Value,
+    ) -> Result<bool, CustomError> {
+        let has_options = request.args.iter().any(|a| {
+            a == FIELD_ARG
+                || a == TRY_DECODE_BASE64
+                || a.starts_with(TRANSFORM_PREFIX)
+                || a.starts_with(SLICE_MODE_PREFIX)
+        });
+
+        let leaf = match lookup_simple_path(value, &request.path) {
+            Some(leaf) if !has_options => leaf,
+            _ => {
+                let data = serde_json::to_vec(value)
+                    .map_err(|e| CustomError::Error(format!("unable to serialize value: {}", e)))?;
+
+                return self.matches(&Request {
+                    data: &data,
+                    ..request.clone()
+                });
+            }
+        };
+
+        if request.match_type == DetectiveType::DETECTIVE_TYPE_UNKNOWN {
+            return Err(CustomError::MatchError(format!(
+                "unknown match type: {:?}",
+                request.match_type
+            )));
+        }
+
+        if self.strict {
+            validate_options(request)?;
+        }
+
+        if request.match_type == DetectiveType::DETECTIVE_TYPE_HAS_FIELD {
+            return Ok(leaf.is_some() != request.negate);
+        }
+
+        let json = match leaf {
+            Some(leaf) => leaf.to_string(),
+            // is_empty() decides what a missing path means, see MISSING_IS_EMPTY
+            None if request.match_type == DetectiveType::DETECTIVE_TYPE_IS_EMPTY => String::new(),
+            None => return Err(CustomError::PathNotFound(request.path.clone())),
+        };
+
+        Ok(self.run_matcher(request, gjson::parse(&json))?.matched != request.negate)
+    }
+
+    /// Resolve `request.path` and convert the value to `T` (see `FromValue`). A
+    /// value `T` can't be converted from is a `CustomError::InvalidField`.
+    /// `request.match_type` and `request.args` are ignored.
+    pub fn extract_as<'a, T: FromValue<'a>>(&self, request: &'a Request) -> Result<T, CustomError> {
+        let field = parse_field(request.data, &request.path)?;
+        let found = JsonType::of(&field).map_or("nothing", |t| t.as_str());
+
+        T::from_value(field).map_err(|err| match err {
+            CustomError::Error(msg) => CustomError::InvalidField(format!(
+                "{} at '{}', found {}",
+                msg, request.path, found
+            )),
+            err => err,
+        })
+    }
+
+    /// Resolve `request.path` as a timestamp (see `matcher_core::parse_timestamp()`)
+    /// and return it as an RFC3339 UTC string.
+    pub fn normalize_timestamp(&self, request: &Request) -> Result<String, CustomError> {
+        let field = parse_field(request.data, &request.path)?;
+        let ts = core::parse_timestamp(&field)?;
+
+        Ok(ts.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+    }
+
+    /// Every match type `matches()` can evaluate, in protobuf declaration order.
+    /// `DETECTIVE_TYPE_UNKNOWN` and types whose matcher isn't implemented yet
+    /// are left out. Derived from `get_matcher_func()`, whose match has no
+    /// wildcard arm, and a probe call that weeds out matchers only returning a
+    /// "not implemented" error, so a new or newly implemented type can't be missed.
+    pub fn supported_match_types() -> Vec<DetectiveType> {
+        let data = Vec::new();
+
+        DetectiveType::VALUES
+            .iter()
+            .filter(|match_type| {
+                let request = Request {
+                    match_type: **match_type,
+                    data: &data,
+                    path: String::new(),
+                    args: Vec::new(),
+                    negate: false,
+                };
+
+                match Detective::get_matcher_func(&request) {
+                    Ok(matcher) => !matches!(
+                        matcher(&request, gjson::parse(r#""""#)),
+                        Err(CustomError::Error(err)) if err == "not implemented"
+                    ),
+                    Err(_) => false,
+                }
+            })
+            .copied()
+            .collect()
+    }
+
+    fn validate_request(&self, request: &Request) -> Result<(), CustomError> {
+        validate_request(request)?;
+
+        if self.strict {
+            validate_options(request)?;
+        }
+
+        Ok(())
+    }
+
+    // Run the request's matcher over a single resolved value, filling in what
+    // MatchResult can report about it
+    fn run_matcher(
+        &self,
+        request: &Request,
+        field: gjson::Value,
+    ) -> Result<MatchResult, CustomError> {
+        let f = self.matcher(request)?;
+        let matched_args = core::matched_args(request, &field);
+        let matched_value = field.exists().then(|| field.str().to_string());
+        let brand = matches!(
+            request.match_type,
+            DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD | DetectiveType::DETECTIVE_TYPE_PII_ANY
+        )
+        .then(|| pii::card_brand(field.str()))
+        .flatten();
+
+        let (matched, detected_type) = if request.match_type == DetectiveType::DETECTIVE_TYPE_PII_ANY {
+            let detected_type = pii::any_detail_with(request, field, &self.overrides)?;
+            (detected_type.is_some(), detected_type)
+        } else {
+            let matched = f(request, field)?;
+            (matched, (matched && is_pii_type(request.match_type)).then_some(request.match_type))
+        };
+
+        let normalized_value = match &matched_value {
+            Some(value)
+                if matched && request.match_type == DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS =>
+            {
+                core::normalize_mac(value, request.args.iter().any(|a| a == core::EUI64))
+            }
+            _ => None,
+        };
+
+        let card_brand = brand.filter(|_| {
+            detected_type == Some(DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD)
+        });
+
+        Ok(MatchResult {
+            matched,
+            matched_args,
+            matched_value,
+            detected_type,
+            card_brand,
+            normalized_value,
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn get_matcher_func(request: &Request) -> Result<MatcherFunc, CustomError> {
         let f: MatcherFunc = match request.match_type {
             DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO
             | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL
@@ -146,25 +1272,219 @@ impl Detective {
     }
 }
 
+fn is_pii_type(match_type: DetectiveType) -> bool {
+    matches!(
+        match_type,
+        DetectiveType::DETECTIVE_TYPE_PII_ANY
+            | DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD
+            | DetectiveType::DETECTIVE_TYPE_PII_SSN
+            | DetectiveType::DETECTIVE_TYPE_PII_EMAIL
+            | DetectiveType::DETECTIVE_TYPE_PII_PHONE
+            | DetectiveType::DETECTIVE_TYPE_PII_DRIVER_LICENSE
+            | DetectiveType::DETECTIVE_TYPE_PII_PASSPORT_ID
+            | DetectiveType::DETECTIVE_TYPE_PII_VIN_NUMBER
+            | DetectiveType::DETECTIVE_TYPE_PII_SERIAL_NUMBER
+            | DetectiveType::DETECTIVE_TYPE_PII_LOGIN
+            | DetectiveType::DETECTIVE_TYPE_PII_TAXPAYER_ID
+            | DetectiveType::DETECTIVE_TYPE_PII_ADDRESS
+            | DetectiveType::DETECTIVE_TYPE_PII_SIGNATURE
+            | DetectiveType::DETECTIVE_TYPE_PII_GEOLOCATION
+            | DetectiveType::DETECTIVE_TYPE_PII_EDUCATION
+            | DetectiveType::DETECTIVE_TYPE_PII_FINANCIAL
+            | DetectiveType::DETECTIVE_TYPE_PII_HEALTH
+    )
+}
+
 pub fn parse_field<'a>(
     data: &'a [u8],
-    path: &'a String,
+    path: &'a str,
 ) -> Result<gjson::Value<'a>, CustomError> {
     let data_as_str = str::from_utf8(data)
         .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
 
+    get_field(data_as_str, path)
+}
+
+fn get_field<'a>(data_as_str: &'a str, path: &'a str) -> Result<gjson::Value<'a>, CustomError> {
     let v = gjson::get(data_as_str, path);
 
     if !v.exists() {
-        return Err(CustomError::Error(format!(
-            "path '{}' not found in data",
-            path
-        )));
+        return Err(CustomError::PathNotFound(path.to_string()));
     }
 
     Ok(v)
 }
 
+/// Like `parse_field()`, but resolves `path` in an already parsed `value` and
+/// converts the result with `FromValue`. Dotted paths of plain keys and array
+/// indexes are looked up directly and only the value found is serialized; other
+/// paths are resolved by gjson on `value` serialized as JSON.
+///
+/// `T` can't borrow from `value`, so `gjson::Value` itself isn't supported.
+pub fn parse_field_from_value<T>(value: &serde_json::Value, path: &str) -> Result<T, CustomError>
+where
+    T: for<'a> FromValue<'a>,
+{
+    let json = match lookup_simple_path(value, path) {
+        Some(Some(leaf)) => leaf.to_string(),
+        Some(None) => return Err(CustomError::PathNotFound(path.to_string())),
+        None => {
+            let json = value.to_string();
+            let field = get_field(&json, path)?;
+
+            field.json().to_string()
+        }
+    };
+
+    T::from_value(gjson::parse(&json))
+}
+
+// Resolve a path made only of plain keys and array indexes (ie. `items.0.name`)
+// in `value`, the same way gjson would. None when the path uses any other gjson
+// syntax, Some(None) when it doesn't exist.
+fn lookup_simple_path<'a>(
+    value: &'a serde_json::Value,
+    path: &str,
+) -> Option<Option<&'a serde_json::Value>> {
+    let is_plain = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+
+    if !path.split('.').all(is_plain) {
+        return None;
+    }
+
+    let mut current = value;
+
+    for segment in path.split('.') {
+        let next = match current {
+            serde_json::Value::Object(map) => map.get(segment),
+            serde_json::Value::Array(items) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        };
+
+        match next {
+            Some(next) => current = next,
+            None => return Some(None),
+        }
+    }
+
+    Some(Some(current))
+}
+
+/// Resolve a path that can select many values, ie. `items.#.price`, returning
+/// the raw JSON of each of them (use `gjson::parse()` to get a `Value`). Any
+/// other path resolves to a single value as with `parse_field()`.
+///
+/// The values of a multi value path are assembled by gjson rather than sliced
+/// from `data`, so they can't be returned as borrowed `Value`s.
+pub fn parse_field_values(data: &[u8], path: &str) -> Result<Vec<String>, CustomError> {
+    let data_as_str = str::from_utf8(data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    get_field_values(data_as_str, path)
+}
+
+fn get_field_values(data_as_str: &str, path: &str) -> Result<Vec<String>, CustomError> {
+    let field = get_field(data_as_str, path)?;
+
+    if is_multi_value_path(path) && field.kind() == gjson::Kind::Array {
+        return Ok(field.array().iter().map(|v| v.json().to_string()).collect());
+    }
+
+    Ok(vec![field.json().to_string()])
+}
+
+// `#.` and `#(...)#` select a value from every element of an array rather than
+// a single value. A bare trailing `#` is the array length.
+fn is_multi_value_path(path: &str) -> bool {
+    path.contains("#.") || path.contains(")#")
+}
+
+// Matchers that match a multi value path when any of the values matches. Other
+// matchers see the values as a single array.
+fn matches_any_value(match_type: DetectiveType) -> bool {
+    matches!(
+        match_type,
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_MIN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE
+            | DetectiveType::DETECTIVE_TYPE_STRING_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY
+            | DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL
+            | DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MIN
+            | DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MAX
+            | DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_RANGE
+            | DetectiveType::DETECTIVE_TYPE_REGEX
+    )
+}
+
+/// Split a path with a `[start:end]` slice qualifier into its parts. Returns
+/// `None` for paths without a slice.
+pub fn parse_path_slice(path: &str) -> Result<Option<PathSlice<'_>>, CustomError> {
+    let caps = match PATH_SLICE_RE.captures(path) {
+        Some(caps) => caps,
+        None => return Ok(None),
+    };
+
+    let bound = |i: usize| -> Result<Option<usize>, CustomError> {
+        match caps.get(i).map(|m| m.as_str()) {
+            None | Some("") => Ok(None),
+            Some(b) => b.parse().map(Some).map_err(|e| {
+                CustomError::Error(format!("invalid slice bound '{}': {}", b, e))
+            }),
+        }
+    };
+
+    Ok(Some(PathSlice {
+        base: caps.get(1).map_or("", |m| m.as_str()),
+        start: bound(2)?.unwrap_or(0),
+        end: bound(3)?,
+        rest: caps.get(4).map(|m| m.as_str()),
+    }))
+}
+
+/// Pull the `SLICE_MODE_PREFIX` option out of `args`, returning the mode and the
+/// remaining args. Without the option the mode is `SliceMode::Any`.
+pub fn parse_slice_mode(args: &[String]) -> Result<(SliceMode, Vec<String>), CustomError> {
+    let mut mode = SliceMode::Any;
+    let mut rest = Vec::new();
+
+    for arg in args {
+        let spec = match arg.strip_prefix(SLICE_MODE_PREFIX) {
+            Some(spec) => spec,
+            None => {
+                rest.push(arg.clone());
+                continue;
+            }
+        };
+
+        let invalid = || CustomError::InvalidArgument(format!("invalid slice mode '{}'", arg));
+
+        mode = match spec.split(':').collect::<Vec<&str>>()[..] {
+            ["any"] => SliceMode::Any,
+            ["all"] => SliceMode::All,
+            ["count", op, count] => SliceMode::Count {
+                op: op.to_string(),
+                count: count.parse().map_err(|_| invalid())?,
+            },
+            _ => return Err(invalid()),
+        };
+    }
+
+    Ok((mode, rest))
+}
+
 pub fn parse_number(input: &str) -> Result<f64, CustomError> {
     match input.parse() {
         Ok(number) => Ok(number),
@@ -175,6 +1495,321 @@ pub fn parse_number(input: &str) -> Result<f64, CustomError> {
     }
 }
 
+/// A stable identifier for the rule in `request`: its match type, path, args
+/// (in order) and negate flag. `data` is ignored, so the same rule evaluated
+/// against different payloads has the same fingerprint. Useful for keying
+/// caches of compiled rules or deduplicating rule sets.
+///
+/// The value is FNV-1a over a length-prefixed encoding of the rule, so it is
+/// the same across processes, platforms and releases of this crate.
+pub fn rule_fingerprint(request: &Request) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+
+    let mut write = |bytes: &[u8]| {
+        for b in bytes {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    // Length prefixes keep ie. args ["ab", "c"] and ["a", "bc"] apart
+    let mut write_str = |s: &str| {
+        write(&(s.len() as u64).to_le_bytes());
+        write(s.as_bytes());
+    };
+
+    write_str(&(request.match_type as i32).to_string());
+    write_str(&request.path);
+    write_str(&request.args.len().to_string());
+
+    for arg in &request.args {
+        write_str(arg);
+    }
+
+    write_str(if request.negate { "negate" } else { "" });
+
+    hash
+}
+
+/// Replace `${name}` references in each arg with `vars[name]`. A reference can
+/// make up the whole arg or part of it (ie. `${MIN}-${MAX}`). Referencing a name
+/// missing from `vars` or leaving a `${` unterminated is an error.
+pub fn substitute_vars(
+    args: &[String],
+    vars: &HashMap<String, String>,
+) -> Result<Vec<String>, CustomError> {
+    args.iter()
+        .map(|arg| {
+            let mut result = String::with_capacity(arg.len());
+            let mut rest = arg.as_str();
+
+            while let Some(start) = rest.find("${") {
+                result.push_str(&rest[..start]);
+
+                let end = rest[start..].find('}').ok_or_else(|| {
+                    CustomError::InvalidArgument(format!("unterminated variable reference in '{}'", arg))
+                })?;
+
+                let name = &rest[start + 2..start + end];
+                let value = vars.get(name).ok_or_else(|| {
+                    CustomError::InvalidArgument(format!("undefined variable '{}' in '{}'", name, arg))
+                })?;
+
+                result.push_str(value);
+                rest = &rest[start + end + 1..];
+            }
+
+            result.push_str(rest);
+
+            Ok(result)
+        })
+        .collect()
+}
+
+/// Compare `left` against `right` using a relational operator given as a
+/// matcher arg: `eq`, `ne`, `gt`, `gte`, `lt`, `lte` (or `==`, `!=`, `>`, `>=`, `<`, `<=`).
+pub fn compare<T: PartialOrd>(op: &str, left: T, right: T) -> Result<bool, CustomError> {
+    match op {
+        "eq" | "==" => Ok(left == right),
+        "ne" | "!=" => Ok(left != right),
+        "gt" | ">" => Ok(left > right),
+        "gte" | ">=" => Ok(left >= right),
+        "lt" | "<" => Ok(left < right),
+        "lte" | "<=" => Ok(left <= right),
+        _ => Err(CustomError::MatchError(format!(
+            "unknown relational operator: {}",
+            op
+        ))),
+    }
+}
+
+// Replace the path in args[0] with the value it resolves to (see FIELD_ARG),
+// erroring when that value's type differs from the value at the request path
+fn resolve_field_arg<'a>(request: &Request<'a>, data_as_str: &str) -> Result<Request<'a>, CustomError> {
+    let supported = matches!(
+        request.match_type,
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_MIN
+            | DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX
+    );
+
+    if !supported {
+        return Err(CustomError::InvalidArgument(format!(
+            "{} is not supported for {:?}",
+            FIELD_ARG, request.match_type
+        )));
+    }
+
+    let mut args: Vec<String> = request
+        .args
+        .iter()
+        .filter(|a| a.as_str() != FIELD_ARG)
+        .cloned()
+        .collect();
+
+    let other_path = args.first().ok_or_else(|| {
+        CustomError::Error(format!("{} requires a path as the first arg", FIELD_ARG))
+    })?;
+
+    let field = get_field(data_as_str, &request.path)?;
+    let other = get_field(data_as_str, other_path)?;
+
+    let (field_type, other_type) = (JsonType::of(&field), JsonType::of(&other));
+
+    if field_type != other_type {
+        return Err(CustomError::InvalidField(format!(
+            "'{}' is {} but '{}' is {}",
+            request.path,
+            field_type.map_or("missing", |t| t.as_str()),
+            other_path,
+            other_type.map_or("missing", |t| t.as_str())
+        )));
+    }
+
+    // Raw JSON for numbers so integers compare exactly
+    args[0] = match other.kind() {
+        gjson::Kind::String => other.str().to_string(),
+        _ => other.json().to_string(),
+    };
+
+    Ok(Request {
+        args,
+        ..request.clone()
+    })
+}
+
+// Find the first prefix of `path` holding a base64 string that decodes to a JSON
+// object or array, returning the decoded JSON and the remainder of the path.
+// `None` if the path already resolves without decoding.
+fn decode_base64_path(data_as_str: &str, path: &str) -> Option<(String, String)> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    if gjson::get(data_as_str, path).exists() {
+        return None;
+    }
+
+    let segments = split_path(path);
+
+    for i in 1..segments.len() {
+        let prefix = segments[..i].join(".");
+        let value = gjson::get(data_as_str, &prefix);
+
+        if value.kind() != gjson::Kind::String {
+            continue;
+        }
+
+        let encoded = value.str().trim();
+
+        let decoded = [STANDARD, URL_SAFE, STANDARD_NO_PAD, URL_SAFE_NO_PAD]
+            .iter()
+            .find_map(|engine| engine.decode(encoded).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+
+        if let Some(json) = decoded {
+            let trimmed = json.trim_start();
+
+            if (trimmed.starts_with('{') || trimmed.starts_with('[')) && gjson::valid(&json) {
+                return Some((json, segments[i..].join(".")));
+            }
+        }
+    }
+
+    None
+}
+
+// Split a path on unescaped dots, keeping the escapes so segments can be rejoined
+fn split_path(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in path.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '.' if !escaped => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+
+    segments.push(&path[start..]);
+
+    segments
+}
+
+// Args each match type understands in strict mode, beyond TRY_DECODE_BASE64, FIELD_ARG,
+// transforms (TRANSFORM_PREFIX) and slice modes (SLICE_MODE_PREFIX) which apply to any
+// path request
+enum ArgSpec {
+    // Matchers that don't take args
+    None,
+    // Only these bare word options
+    Options(&'static [&'static str]),
+    // Numbers (or intervals for ranges) plus these options
+    Numeric(&'static [&'static str]),
+    // Args are free form values, ie. strings to compare against
+    Any,
+}
+
+fn arg_spec(match_type: DetectiveType) -> ArgSpec {
+    match match_type {
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_MIN
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE => {
+            ArgSpec::Numeric(&[numeric::REJECT_LEADING_ZEROS, numeric::DECIMAL])
+        }
+        DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MIN
+        | DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MAX
+        | DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_RANGE
+        | DetectiveType::DETECTIVE_TYPE_PII_ANY => ArgSpec::Numeric(&[]),
+        DetectiveType::DETECTIVE_TYPE_IS_EMPTY => ArgSpec::Options(&[core::MISSING_IS_EMPTY]),
+        DetectiveType::DETECTIVE_TYPE_PII_GEOLOCATION => ArgSpec::Options(&[pii::DMS]),
+        DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS => ArgSpec::Options(&[core::EUI64]),
+        DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD => {
+            ArgSpec::Options(&[pii::IGNORE_TEST_VALUES, pii::SKIP_LUHN])
+        }
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL => ArgSpec::Options(&[pii::IGNORE_TEST_VALUES]),
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL
+        | DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY
+        | DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL
+        | DetectiveType::DETECTIVE_TYPE_REGEX
+        | DetectiveType::DETECTIVE_TYPE_IS_TYPE
+        | DetectiveType::DETECTIVE_TYPE_PII_PHONE
+        | DetectiveType::DETECTIVE_TYPE_UNKNOWN => ArgSpec::Any,
+        _ => ArgSpec::None,
+    }
+}
+
+/// Reject args the request's matcher doesn't understand. Only enforced by a
+/// detective created with `Detective::new_strict()`; otherwise matchers ignore
+/// args they don't use.
+pub fn validate_options(request: &Request) -> Result<(), CustomError> {
+    let unknown = |arg: &str| {
+        CustomError::InvalidArgument(format!(
+            "unknown option '{}' for {:?}",
+            arg, request.match_type
+        ))
+    };
+
+    let args = request
+        .args
+        .iter()
+        .filter(|a| {
+            request.path.is_empty()
+                || (a.as_str() != TRY_DECODE_BASE64
+                    && a.as_str() != FIELD_ARG
+                    && !a.starts_with(TRANSFORM_PREFIX)
+                    && !a.starts_with(SLICE_MODE_PREFIX))
+        })
+        // With FIELD_ARG the first arg is a path, not a value to validate
+        .skip(usize::from(!request.path.is_empty() && request.args.iter().any(|a| a == FIELD_ARG)));
+
+    match arg_spec(request.match_type) {
+        ArgSpec::Any => Ok(()),
+        ArgSpec::None => match args.into_iter().next() {
+            Some(arg) => Err(unknown(arg)),
+            None => Ok(()),
+        },
+        ArgSpec::Options(options) => {
+            for arg in args {
+                if !options.contains(&arg.as_str()) {
+                    return Err(unknown(arg));
+                }
+            }
+
+            Ok(())
+        }
+        ArgSpec::Numeric(options) => {
+            for arg in args {
+                let is_interval = request.match_type == DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE
+                    && numeric::parse_interval(arg).is_ok();
+
+                if !options.contains(&arg.as_str()) && parse_number(arg).is_err() && !is_interval {
+                    return Err(unknown(arg));
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
 fn validate_request(request: &Request) -> Result<(), CustomError> {
     if request.match_type == DetectiveType::DETECTIVE_TYPE_UNKNOWN {
         return Err(CustomError::MatchError(format!(
@@ -189,7 +1824,7 @@ fn validate_request(request: &Request) -> Result<(), CustomError> {
 
     Ok(())
 }
-fn recurse_field(request: &Request, val: gjson::Value, f: MatcherFunc) -> bool {
+fn recurse_field(request: &Request, val: gjson::Value, f: &MatcherRef) -> bool {
     match val.kind() {
         gjson::Kind::String | gjson::Kind::Number | gjson::Kind::True | gjson::Kind::False => {
             if let Ok(res) = f(request, val) {
@@ -227,4 +1862,95 @@ fn recurse_field(request: &Request, val: gjson::Value, f: MatcherFunc) -> bool {
     }
 
     false
-}
\ No newline at end of file
+}
+fn count_pii(
+    data: &Vec<u8>,
+    val: gjson::Value,
+    overrides: &pii::PatternOverrides,
+    summary: &mut HashMap<DetectiveType, usize>,
+) {
+    match val.kind() {
+        gjson::Kind::String | gjson::Kind::Number | gjson::Kind::True | gjson::Kind::False => {
+            for (match_type, f) in pii::PII_DETECTORS {
+                let request = Request {
+                    match_type: *match_type,
+                    data,
+                    path: String::new(),
+                    args: vec![],
+                    negate: false,
+                };
+
+                let hit = match overrides.get(match_type) {
+                    Some(re) => re.is_match(val.str()),
+                    None => matches!(f(&request, gjson::parse(val.json())), Ok(true)),
+                };
+
+                if hit {
+                    *summary.entry(*match_type).or_insert(0) += 1;
+                }
+            }
+        }
+        gjson::Kind::Object | gjson::Kind::Array => {
+            val.each(|_, value| {
+                count_pii(data, value, overrides, summary);
+                true
+            });
+        }
+        _ => {}
+    }
+}
+
+fn collect_matching_paths(
+    request: &Request,
+    val: gjson::Value,
+    path: &str,
+    f: &MatcherRef,
+    paths: &mut Vec<String>,
+) {
+    match val.kind() {
+        gjson::Kind::String | gjson::Kind::Number | gjson::Kind::True | gjson::Kind::False => {
+            if let Ok(true) = f(request, val) {
+                paths.push(path.to_string());
+            }
+        }
+        gjson::Kind::Object | gjson::Kind::Array => {
+            let is_array = val.kind() == gjson::Kind::Array;
+            let mut index = 0;
+
+            val.each(|key, value| {
+                let segment = if is_array {
+                    index.to_string()
+                } else {
+                    escape_path_segment(key.str())
+                };
+
+                index += 1;
+
+                let child_path = if path.is_empty() {
+                    segment
+                } else {
+                    format!("{}.{}", path, segment)
+                };
+
+                collect_matching_paths(request, value, &child_path, f, paths);
+                true
+            });
+        }
+        _ => {} // Don't care about nulls
+    }
+}
+
+// gjson treats these as path syntax, so they need escaping when they're part of a key
+fn escape_path_segment(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+
+    for c in key.chars() {
+        if matches!(c, '.' | '*' | '?' | '|' | '#' | '@' | '\\') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
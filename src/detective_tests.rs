@@ -0,0 +1,1834 @@
+use crate::detective::{
+    parse_field_from_value, rule_fingerprint, substitute_vars, Detective, Document, JsonType,
+    Request, RuleSet, FIELD_ARG, TRY_DECODE_BASE64,
+};
+use crate::error::CustomError;
+use std::collections::HashMap;
+#[cfg(test)]
+use protos::sp_steps_detective::DetectiveType;
+
+#[test]
+fn test_matches_named() {
+    let det = Detective::new();
+    let data = r#"{"name": "bob"}"#.as_bytes().to_vec();
+
+    let mut request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        data: &data,
+        path: "name".to_string(),
+        args: vec!["eq".to_string(), "2".to_string()],
+        negate: false,
+    };
+
+    assert!(det.matches_named("distinct_chars", &request).unwrap());
+
+    request.negate = true;
+    assert!(!det.matches_named("distinct_chars", &request).unwrap());
+
+    assert!(matches!(
+        det.matches_named("bogus", &request),
+        Err(CustomError::MatchError(_))
+    ));
+}
+
+#[test]
+fn test_matches_value() {
+    let det = Detective::new();
+
+    let value = gjson::parse(r#""test@example.com""#);
+    assert!(det
+        .matches_value(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, vec![], false, value)
+        .unwrap());
+
+    let value = gjson::parse("100.1");
+    assert!(det
+        .matches_value(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            vec!["100".to_string()],
+            false,
+            value
+        )
+        .unwrap());
+
+    let value = gjson::parse("100.1");
+    assert!(!det
+        .matches_value(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            vec!["100".to_string()],
+            true,
+            value
+        )
+        .unwrap());
+
+    let value = gjson::parse(r#"["value1", "value2"]"#);
+    assert!(det
+        .matches_value(
+            DetectiveType::DETECTIVE_TYPE_IS_TYPE,
+            vec!["array".to_string()],
+            false,
+            value
+        )
+        .unwrap());
+
+    let value = gjson::parse(r#""value""#);
+    assert!(det
+        .matches_value(DetectiveType::DETECTIVE_TYPE_HAS_FIELD, vec![], false, value)
+        .is_err());
+
+    let value = gjson::parse(r#""value""#);
+    assert!(det
+        .matches_value(DetectiveType::DETECTIVE_TYPE_UNKNOWN, vec![], false, value)
+        .is_err());
+}
+
+#[test]
+fn test_normalize_timestamp() {
+    let det = Detective::new();
+
+    let paths = vec![
+        "timestamp_unix_str",
+        "timestamp_unix_num",
+        "timestamp_unix_milli_num",
+        "timestamp_unix_nano_str",
+        "timestamp_unix_nano_num",
+        "timestamp_rfc3339_offset",
+    ];
+
+    for path in paths {
+        let request = crate::test_utils::generate_request_for_bench(
+            DetectiveType::DETECTIVE_TYPE_TIMESTAMP_RFC3339,
+            path,
+            vec![],
+        );
+
+        assert_eq!(
+            det.normalize_timestamp(&request).unwrap(),
+            "2021-03-01T00:00:00Z",
+            "{}",
+            path
+        );
+    }
+
+    let request = crate::test_utils::generate_request_for_bench(
+        DetectiveType::DETECTIVE_TYPE_TIMESTAMP_RFC3339,
+        "object.field",
+        vec![],
+    );
+
+    assert!(det.normalize_timestamp(&request).is_err());
+}
+
+#[test]
+fn test_array_slice() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["2".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "element within slice should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["100".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "element outside slice should be ignored".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[3:]".to_string(),
+                args: vec!["100".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "open ended slice should include the tail".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[:100]".to_string(),
+                args: vec!["100".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "slice past the end should be clamped".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[10:20]".to_string(),
+                args: vec!["0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "slice starting past the end should be empty".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "items[0:3].price".to_string(),
+                args: vec!["1000".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "sub-path outside slice should be ignored".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "items[1:].price".to_string(),
+                args: vec!["1000".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "sub-path within slice should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "number_int[0:3]".to_string(),
+                args: vec!["1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "slice of a non-array should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["0".to_string(), "slice:all".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "every element within slice should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["1".to_string(), "slice:all".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "slice:all should not match when one element doesn't".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["1".to_string(), "slice:count:eq:2".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "two elements within slice should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["1".to_string(), "slice:count:gte:3".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "element outside slice should not be counted".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[10:20]".to_string(),
+                args: vec!["0".to_string(), "slice:count:eq:0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "empty slice should have a count of 0".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "numbers[0:3]".to_string(),
+                args: vec!["1".to_string(), "slice:most".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown slice mode should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["1".to_string(), "slice:all".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "slice mode without a slice path should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_find_matching_paths() {
+    let data = r#"{
+        "user": {
+            "email": "bob@streamdal.com",
+            "name": "bob",
+            "contacts": [
+                {"email": "alice@streamdal.com"},
+                {"email": "not an email"},
+                {"email": "carol@streamdal.com"}
+            ]
+        },
+        "support.email": "support@streamdal.com",
+        "count": 3
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let det = Detective::new();
+
+    let paths = det
+        .find_matching_paths(&data, DetectiveType::DETECTIVE_TYPE_PII_EMAIL, vec![])
+        .unwrap();
+
+    assert_eq!(
+        paths,
+        vec![
+            "user.email",
+            "user.contacts.0.email",
+            "user.contacts.2.email",
+            "support\\.email",
+        ]
+    );
+
+    // Every returned path should resolve back to a matching value
+    for path in paths {
+        let request = Request {
+            match_type: DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+            data: &data,
+            path,
+            args: vec![],
+            negate: false,
+        };
+
+        assert!(det.matches(&request).unwrap(), "{}", request.path);
+    }
+
+    assert!(det
+        .find_matching_paths(&data, DetectiveType::DETECTIVE_TYPE_UNKNOWN, vec![])
+        .is_err());
+}
+
+#[test]
+fn test_matches_document() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let doc = Document::parse(&data).unwrap();
+
+    let rules = vec![
+        (
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "object.field".to_string(),
+            vec!["value".to_string()],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            "number_int".to_string(),
+            vec!["1000".to_string()],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "does.not.exist".to_string(),
+            vec!["value".to_string()],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            "numbers[0:5]".to_string(),
+            vec!["100".to_string()],
+        ),
+        (DetectiveType::DETECTIVE_TYPE_PII_EMAIL, String::new(), vec![]),
+    ];
+
+    let results = det.matches_document(&doc, &rules);
+
+    assert_eq!(results.len(), rules.len());
+    assert!(results[0].as_ref().unwrap());
+    assert!(!results[1].as_ref().unwrap());
+    assert!(results[2].is_err(), "missing path should only fail its own rule");
+    assert!(results[3].as_ref().unwrap());
+    assert!(results[4].as_ref().unwrap());
+
+    // Results should be identical to calling matches() per rule
+    for ((match_type, path, args), result) in rules.iter().zip(&results) {
+        let request = Request {
+            match_type: *match_type,
+            data: &data,
+            path: path.clone(),
+            args: args.clone(),
+            negate: false,
+        };
+
+        assert_eq!(det.matches(&request).ok(), result.as_ref().ok().copied(), "{}", path);
+    }
+
+    assert!(Document::parse(&Vec::new()).is_err());
+    assert!(Document::parse(&vec![0xff, 0xfe]).is_err());
+}
+
+#[test]
+fn test_matches_detailed_value_type() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let detailed = |match_type: DetectiveType, path: &str, args: Vec<&str>| {
+        det.matches_detailed(&Request {
+            match_type,
+            data: &data,
+            path: path.to_string(),
+            args: args.into_iter().map(String::from).collect(),
+            negate: false,
+        })
+    };
+
+    let test_cases = vec![
+        ("object.field", Some(JsonType::String)),
+        ("number_int", Some(JsonType::Number)),
+        ("boolean_t", Some(JsonType::Bool)),
+        ("boolean_f", Some(JsonType::Bool)),
+        ("numbers", Some(JsonType::Array)),
+        ("object", Some(JsonType::Object)),
+        ("object.null_field", Some(JsonType::Null)),
+        ("does.not.exist", None),
+    ];
+
+    for (path, expected) in test_cases {
+        let result = detailed(DetectiveType::DETECTIVE_TYPE_HAS_FIELD, path, vec![]).unwrap();
+
+        assert_eq!(result.value_type, expected, "{}", path);
+        assert_eq!(result.matched, expected.is_some(), "{}", path);
+    }
+
+    // A string matcher on a number coerces, the type shows what was actually there
+    let result = detailed(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "number_int",
+        vec!["100"],
+    )
+    .unwrap();
+
+    assert_eq!(result.value_type, Some(JsonType::Number));
+    assert_eq!(JsonType::Number.as_str(), "number");
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "", vec![]).unwrap();
+    assert!(result.matched);
+    assert_eq!(result.value_type, None);
+
+    assert!(detailed(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "does.not.exist",
+        vec!["value"]
+    )
+    .is_err());
+}
+
+#[test]
+fn test_matches_detailed_matched_args() {
+    let det = Detective::new();
+    let data = r#"{"comment": "Buy cheap Pills at SPAM.example"}"#.as_bytes().to_vec();
+
+    let detailed = |match_type: DetectiveType, args: Vec<&str>, negate: bool| {
+        det.matches_detailed(&Request {
+            match_type,
+            data: &data,
+            path: "comment".to_string(),
+            args: args.into_iter().map(String::from).collect(),
+            negate,
+        })
+        .unwrap()
+    };
+
+    let result = detailed(
+        DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY,
+        vec!["casino", "cheap", "lottery", "SPAM"],
+        false,
+    );
+    assert!(result.matched);
+    assert_eq!(result.matched_args, vec!["cheap", "SPAM"]);
+
+    // Args are reported as given, not case folded
+    let result = detailed(
+        DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL,
+        vec!["pills", "Spam", "free", "ignore_case"],
+        false,
+    );
+    assert!(!result.matched);
+    assert_eq!(result.matched_args, vec!["pills", "Spam"]);
+
+    // Negate doesn't hide which terms appeared
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY, vec!["cheap"], true);
+    assert!(!result.matched);
+    assert_eq!(result.matched_args, vec!["cheap"]);
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY, vec!["casino"], false);
+    assert!(result.matched_args.is_empty());
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MIN, vec!["5"], false);
+    assert!(result.matched_args.is_empty());
+}
+
+#[test]
+fn test_matches_detailed_value_and_detected_type() {
+    let det = Detective::new();
+    let data = r#"{"contact": "jane@example.com", "age": 42, "note": "hello"}"#
+        .as_bytes()
+        .to_vec();
+
+    let detailed = |match_type: DetectiveType, path: &str, args: Vec<&str>| {
+        det.matches_detailed(&Request {
+            match_type,
+            data: &data,
+            path: path.to_string(),
+            args: args.into_iter().map(String::from).collect(),
+            negate: false,
+        })
+        .unwrap()
+    };
+
+    // PII_ANY reports which detector hit
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_ANY, "contact", vec![]);
+    assert!(result.matched);
+    assert_eq!(result.matched_value.as_deref(), Some("jane@example.com"));
+    assert_eq!(result.detected_type, Some(DetectiveType::DETECTIVE_TYPE_PII_EMAIL));
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_ANY, "note", vec![]);
+    assert!(!result.matched);
+    assert_eq!(result.detected_type, None);
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "contact", vec![]);
+    assert_eq!(result.detected_type, Some(DetectiveType::DETECTIVE_TYPE_PII_EMAIL));
+
+    // Non-strings are reported as raw JSON, and only PII types detect anything
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "age", vec!["18"]);
+    assert!(result.matched);
+    assert_eq!(result.matched_value.as_deref(), Some("42"));
+    assert_eq!(result.detected_type, None);
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_HAS_FIELD, "missing", vec![]);
+    assert_eq!(result.matched_value, None);
+
+    // matches() is the matched flag of matches_detailed()
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_PII_ANY,
+        data: &data,
+        path: "contact".to_string(),
+        args: vec![],
+        negate: true,
+    };
+    assert!(!det.matches(&request).unwrap());
+    assert!(det.matches_detailed(&request).unwrap().detected_type.is_some());
+}
+
+#[test]
+fn test_matches_array_unique() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |path: &str, args: Vec<&str>| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        data: &data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate: false,
+    };
+
+    let result = det
+        .matches_array_unique(&request("users_unique", vec!["id"]))
+        .unwrap();
+
+    assert!(result.matched);
+    assert_eq!(result.duplicate, None);
+    assert_eq!(result.value_type, Some(JsonType::Array));
+
+    let result = det
+        .matches_array_unique(&request("users_duplicate", vec!["id"]))
+        .unwrap();
+
+    assert!(!result.matched);
+    assert_eq!(result.duplicate, Some("2".to_string()));
+
+    // Whole elements are compared without a sub-path
+    let result = det
+        .matches_array_unique(&request("users_duplicate", vec![]))
+        .unwrap();
+
+    assert!(!result.matched);
+    assert_eq!(result.duplicate, Some(r#"{"id": 2}"#.to_string()));
+
+    assert!(det
+        .matches_array_unique(&request("does.not.exist", vec![]))
+        .is_err());
+}
+
+#[test]
+fn test_matches_array_sorted() {
+    let det = Detective::new();
+    let data = r#"{
+        "ascending": [1, 2, 2, 10, 35.5],
+        "descending": ["pear", "orange", "banana", "apple"],
+        "unsorted": [3, 5, 4, 6, 1],
+        "empty": [],
+        "mixed": [1, "2", 3]
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let request = |path: &str, direction: &str| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        data: &data,
+        path: path.to_string(),
+        args: vec![direction.to_string()],
+        negate: false,
+    };
+
+    let result = det.matches_array_sorted(&request("ascending", "asc")).unwrap();
+    assert!(result.matched);
+    assert_eq!(result.unsorted_index, None);
+
+    let result = det.matches_array_sorted(&request("descending", "desc")).unwrap();
+    assert!(result.matched);
+
+    // Reverse sorted is out of order from the second element
+    let result = det.matches_array_sorted(&request("descending", "asc")).unwrap();
+    assert!(!result.matched);
+    assert_eq!(result.unsorted_index, Some(1));
+
+    let result = det.matches_array_sorted(&request("unsorted", "asc")).unwrap();
+    assert!(!result.matched);
+    assert_eq!(result.unsorted_index, Some(2));
+
+    assert!(det.matches_array_sorted(&request("empty", "desc")).unwrap().matched);
+    assert!(det.matches_array_sorted(&request("mixed", "asc")).is_err());
+    assert!(det.matches_array_sorted(&request("ascending", "sideways")).is_err());
+    assert!(det.matches_array_sorted(&request("does.not.exist", "asc")).is_err());
+}
+
+#[test]
+fn test_try_decode_base64() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |match_type: DetectiveType, path: &str, args: Vec<&str>| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate: false,
+    };
+
+    let result = det
+        .matches_detailed(&request(
+            DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+            "encoded_payload.user.email",
+            vec![TRY_DECODE_BASE64],
+        ))
+        .unwrap();
+
+    assert!(result.matched);
+    assert!(result.decoded);
+    assert_eq!(result.value_type, Some(JsonType::String));
+
+    // The option is stripped before the args reach the matcher
+    assert!(det
+        .matches(&request(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+            "encoded_payload.user.age",
+            vec!["42", TRY_DECODE_BASE64],
+        ))
+        .unwrap());
+
+    assert!(det
+        .matches(&request(
+            DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+            "encoded_payload.user",
+            vec![TRY_DECODE_BASE64],
+        ))
+        .unwrap());
+
+    // Paths that resolve as-is aren't decoded
+    let result = det
+        .matches_detailed(&request(
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "object.field",
+            vec!["value", TRY_DECODE_BASE64],
+        ))
+        .unwrap();
+
+    assert!(result.matched);
+    assert!(!result.decoded);
+
+    // Without the option the path doesn't exist
+    assert!(det
+        .matches(&request(
+            DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+            "encoded_payload.user.email",
+            vec![],
+        ))
+        .is_err());
+
+    // Strings that aren't base64 of JSON aren't decoded
+    assert!(det
+        .matches(&request(
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "object.field.user",
+            vec!["value", TRY_DECODE_BASE64],
+        ))
+        .is_err());
+}
+
+#[test]
+fn test_strict_mode() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |match_type: DetectiveType, path: &str, args: Vec<&str>| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate: false,
+    };
+
+    let lenient = Detective::new();
+    let strict = Detective::new_strict();
+
+    // Misspelled option is silently ignored unless strict
+    let typo = request(
+        DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD,
+        "object.credit_card.visa.valid",
+        vec!["ignore_test_value"],
+    );
+
+    assert!(lenient.matches(&typo).unwrap());
+
+    let err = strict.matches(&typo).unwrap_err();
+    assert!(matches!(err, crate::error::CustomError::InvalidArgument(_)), "{:?}", err);
+
+    let valid = vec![
+        request(
+            DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD,
+            "object.credit_card.visa.valid",
+            vec!["ignore_test_values"],
+        ),
+        request(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            "number_int",
+            vec!["10", "reject_leading_zeros"],
+        ),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["[0,1000)"]),
+        request(
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "object.field",
+            vec!["anything goes"],
+        ),
+        request(
+            DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+            "encoded_payload.user.email",
+            vec![TRY_DECODE_BASE64],
+        ),
+    ];
+
+    for req in valid {
+        assert!(strict.matches(&req).is_ok(), "{:?} {:?}", req.match_type, req.args);
+    }
+
+    let invalid = vec![
+        request(DetectiveType::DETECTIVE_TYPE_UUID, "object.uuid_dash", vec!["strict"]),
+        request(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            "number_int",
+            vec!["10", "reject_leading_zero"],
+        ),
+        request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "", vec![TRY_DECODE_BASE64]),
+    ];
+
+    for req in invalid {
+        assert!(strict.matches(&req).is_err(), "{:?} {:?}", req.match_type, req.args);
+    }
+}
+
+#[test]
+fn test_pii_summary() {
+    let det = Detective::new();
+
+    let data = r#"{
+        "customer": {
+            "email": "jsmith@streamdal.com",
+            "ssn": "123-45-6789",
+            "contacts": [
+                {"email": "alice@streamdal.com"},
+                {"email": "bob@streamdal.com", "note": "not pii"}
+            ]
+        },
+        "order_id": 12345,
+        "active": true
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let summary = det.pii_summary(&data).unwrap();
+
+    assert_eq!(summary.get(&DetectiveType::DETECTIVE_TYPE_PII_EMAIL), Some(&3));
+    assert_eq!(summary.get(&DetectiveType::DETECTIVE_TYPE_PII_SSN), Some(&1));
+    assert_eq!(summary.get(&DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD), None);
+    assert_eq!(summary.len(), 2);
+
+    let summary = det.pii_summary(&r#"{"a": "nothing here"}"#.as_bytes().to_vec()).unwrap();
+    assert!(summary.is_empty());
+
+    assert!(det.pii_summary(&Vec::new()).is_err());
+}
+
+#[test]
+fn test_matches_with_vars() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+        data: &data,
+        path: "number_int".to_string(),
+        args: vec!["${THRESHOLD}".to_string()],
+        negate: false,
+    };
+
+    let mut vars = HashMap::new();
+
+    vars.insert("THRESHOLD".to_string(), "50".to_string());
+    assert!(det.matches_with_vars(&request, &vars).unwrap());
+
+    // Same rule, different threshold at match time
+    vars.insert("THRESHOLD".to_string(), "500".to_string());
+    assert!(!det.matches_with_vars(&request, &vars).unwrap());
+
+    let err = det.matches_with_vars(&request, &HashMap::new()).unwrap_err();
+    assert!(err.to_string().contains("THRESHOLD"), "{}", err);
+
+    // Without substitution the reference is just a bad arg
+    assert!(det.matches(&request).is_err());
+
+    vars.insert("LOW".to_string(), "0".to_string());
+    vars.insert("HIGH".to_string(), "1000".to_string());
+
+    assert_eq!(
+        substitute_vars(
+            &["[${LOW},${HIGH})".to_string(), "plain".to_string(), "$5".to_string()],
+            &vars
+        )
+        .unwrap(),
+        vec!["[0,1000)", "plain", "$5"]
+    );
+
+    assert!(substitute_vars(&["${LOW".to_string()], &vars).is_err());
+}
+
+#[test]
+fn test_diagnose_pii() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |path: &str, args: Vec<String>| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_PII_ANY,
+        data: &data,
+        path: path.to_string(),
+        args,
+        negate: false,
+    };
+
+    let reason = |diagnoses: &Vec<crate::matcher_pii::PiiDiagnosis>, match_type| {
+        diagnoses
+            .iter()
+            .find(|d| d.match_type == match_type)
+            .map(|d| (d.matched, d.reason.clone()))
+            .unwrap()
+    };
+
+    // A near-miss card: right length, bad checksum
+    let diagnoses = det.diagnose_pii(&request("object.credit_card.visa.invalid", vec![]));
+
+    assert_eq!(diagnoses.len(), crate::matcher_pii::PII_DETECTORS.len());
+    assert_eq!(
+        reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD),
+        (false, "16 digits found but Luhn failed".to_string())
+    );
+    assert_eq!(
+        reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_SSN),
+        (false, "not formatted as AAA-GG-SSSS".to_string())
+    );
+    assert!(!reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_EMAIL).0);
+
+    let diagnoses = det.diagnose_pii(&request("object.credit_card.visa.real", vec![]));
+    assert_eq!(
+        reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD),
+        (true, "matched with confidence 0.9".to_string())
+    );
+
+    let diagnoses = det.diagnose_pii(&request(
+        "object.credit_card.visa.valid",
+        vec![crate::matcher_pii::IGNORE_TEST_VALUES.to_string()],
+    ));
+    assert_eq!(
+        reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD),
+        (false, "known test card number ignored".to_string())
+    );
+
+    let diagnoses = det.diagnose_pii(&request("object.email_plain_invalid", vec![]));
+    assert_eq!(
+        reason(&diagnoses, DetectiveType::DETECTIVE_TYPE_PII_EMAIL),
+        (false, "domain 'example' has no '.'".to_string())
+    );
+
+    let diagnoses = det.diagnose_pii(&request("does.not.exist", vec![]));
+    assert!(diagnoses.iter().all(|d| !d.matched && d.reason.contains("not found")));
+}
+
+#[test]
+fn test_diagnose_pii_overrides() {
+    let mut det = Detective::new();
+    let data = r#"{"contact": "jane@internal", "other": "jane@example.org"}"#
+        .as_bytes()
+        .to_vec();
+
+    let request = |path: &str| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_PII_ANY,
+        data: &data,
+        path: path.to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    let email = |diagnoses: Vec<crate::matcher_pii::PiiDiagnosis>| {
+        diagnoses
+            .into_iter()
+            .find(|d| d.match_type == DetectiveType::DETECTIVE_TYPE_PII_EMAIL)
+            .map(|d| (d.matched, d.reason))
+            .unwrap()
+    };
+
+    assert!(!email(det.diagnose_pii(&request("contact"))).0);
+
+    det.set_pattern_overrides(HashMap::from([(
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+        r"^[a-z]+@internal$".to_string(),
+    )]))
+    .unwrap();
+
+    assert_eq!(
+        email(det.diagnose_pii(&request("contact"))),
+        (true, "matched custom pattern".to_string())
+    );
+    assert_eq!(
+        email(det.diagnose_pii(&request("other"))),
+        (false, "did not match custom pattern".to_string())
+    );
+
+    // Detectors without an override keep their built-in diagnosis
+    let diagnoses = det.diagnose_pii(&request("contact"));
+    assert!(diagnoses
+        .iter()
+        .find(|d| d.match_type == DetectiveType::DETECTIVE_TYPE_PII_SSN)
+        .is_some_and(|d| d.reason == "not formatted as AAA-GG-SSSS"));
+}
+
+#[test]
+fn test_matches_many() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let other = r#"{"object": {"field": "other"}}"#.as_bytes().to_vec();
+    let invalid = vec![0xff, 0xfe];
+
+    let request = |match_type, data, path: &str, args: Vec<&str>, negate| Request {
+        match_type,
+        data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate,
+    };
+
+    let requests = vec![
+        request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, &data, "object.field", vec!["value"], false),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, &data, "number_int", vec!["1000"], false),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, &data, "number_int", vec!["1000"], true),
+        request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, &data, "does.not.exist", vec!["value"], false),
+        request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, &data, "", vec![], false),
+        // Different data is still evaluated against its own payload
+        request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, &other, "object.field", vec!["other"], false),
+        request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, &invalid, "object.field", vec!["value"], false),
+    ];
+
+    let results = det.matches_many(&requests);
+
+    assert_eq!(results.len(), requests.len());
+    assert!(results[0].as_ref().unwrap());
+    assert!(!results[1].as_ref().unwrap());
+    assert!(results[2].as_ref().unwrap(), "negate should be respected");
+    assert!(results[3].is_err(), "missing path should only fail its own request");
+    assert!(results[4].as_ref().unwrap());
+    assert!(results[5].as_ref().unwrap());
+    assert!(results[6].is_err());
+
+    // Results should be identical to calling matches() per request
+    for (request, result) in requests.iter().zip(&results) {
+        assert_eq!(det.matches(request).ok(), result.as_ref().ok().copied(), "{}", request.path);
+    }
+
+    assert!(det.matches_many(&[]).is_empty());
+}
+
+#[test]
+fn test_negate() {
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |match_type, path: &str, args: Vec<&str>, negate| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate,
+    };
+
+    let cases = vec![
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "number_int", vec!["50"], true),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "number_int", vec!["500"], false),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["[0,100]"], true),
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "object.field", vec!["value"], true),
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "object.field", vec!["other"], false),
+        (DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "", vec![], true),
+    ];
+
+    for (match_type, path, args, expected) in cases {
+        let plain = request(match_type, path, args.clone(), false);
+        let negated = request(match_type, path, args, true);
+
+        assert_eq!(det.matches(&plain).unwrap(), expected, "{:?} {}", match_type, path);
+        assert_eq!(det.matches(&negated).unwrap(), !expected, "negated {:?} {}", match_type, path);
+        assert_eq!(det.matches_detailed(&negated).unwrap().matched, !expected, "detailed {:?} {}", match_type, path);
+        assert_eq!(det.matches_many(&[negated])[0].as_ref().unwrap(), &!expected, "many {:?} {}", match_type, path);
+    }
+
+    // Errors stay errors
+    let missing = request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "does.not.exist", vec!["value"], true);
+    assert!(det.matches(&missing).is_err());
+
+    let bad_arg = request(DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "number_int", vec!["abc"], true);
+    assert!(det.matches(&bad_arg).is_err());
+
+    let value = gjson::parse(r#""jsmith@streamdal.com""#);
+    assert!(!det
+        .matches_value(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, vec![], true, value)
+        .unwrap());
+}
+
+#[test]
+fn test_rule_fingerprint() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let other_data = r#"{"number_int": 1}"#.as_bytes().to_vec();
+
+    let request = |data, match_type, path: &str, args: Vec<&str>, negate| Request {
+        match_type,
+        data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate,
+    };
+
+    let base = request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "10"], false);
+    let fingerprint = rule_fingerprint(&base);
+
+    // Equal rules hash equal, whatever the data
+    assert_eq!(fingerprint, rule_fingerprint(&base.clone()));
+    assert_eq!(
+        fingerprint,
+        rule_fingerprint(&request(&other_data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "10"], false))
+    );
+
+    let different = vec![
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "11"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["10", "1"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["11", "0"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "10", "reject_leading_zeros"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "10"], true),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_float", vec!["1", "10"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_RANGE, "number_int", vec!["1", "10"], false),
+        request(&data, DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["11", ""], false),
+    ];
+
+    for rule in &different {
+        assert_ne!(fingerprint, rule_fingerprint(rule), "{:?} {}", rule.args, rule.path);
+    }
+
+    // Stable across releases: pinned value
+    assert_eq!(
+        rule_fingerprint(&request(&data, DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "a", vec!["b"], false)),
+        0xc41148a5f6d715ac
+    );
+}
+
+#[test]
+fn test_multi_value_paths() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "line_items.#.price".to_string(),
+                args: vec!["1000".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any line item over 1000 should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "line_items.#.price".to_string(),
+                args: vec!["5000".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "no line item over 5000 should not match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN,
+                data: sample_json,
+                path: "line_items.#.price".to_string(),
+                args: vec!["30".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any line item under 30 should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "line_items.#.price".to_string(),
+                args: vec!["1000".to_string()],
+                negate: true,
+            },
+            expected: false,
+            text: "negate applies to the combined result".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "items.#.price".to_string(),
+                args: vec!["[25,35]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "interval should match any element".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "line_items.#.status".to_string(),
+                args: vec!["backordered".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any element equal should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "line_items.#.status".to_string(),
+                args: vec!["cancelled".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "no element equal should not match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "line_items.#(price>1000).sku".to_string(),
+                args: vec!["B-200".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "single value query should match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "line_items.#(price<100)#.sku".to_string(),
+                args: vec!["C-300".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "multi value query should match any element".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "line_items_empty.#.price".to_string(),
+                args: vec!["0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "empty array should not match".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "line_items.#".to_string(),
+                args: vec!["3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "a bare # is still the array length".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "line_items.#.sku".to_string(),
+                args: vec!["0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-numeric element should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_multi_value_path_elements() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let det = Detective::new();
+
+    let request = |path: &str, negate| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+        data: &data,
+        path: path.to_string(),
+        args: vec!["50".to_string()],
+        negate,
+    };
+
+    let result = det.matches_detailed(&request("line_items.#.price", false)).unwrap();
+
+    assert!(result.matched);
+    assert_eq!(
+        result.elements,
+        vec![
+            ("line_items.#.price|0".to_string(), false),
+            ("line_items.#.price|1".to_string(), true),
+            ("line_items.#.price|2".to_string(), true),
+        ]
+    );
+
+    // Each element path resolves to the value it was evaluated against
+    let prices: Vec<f64> = result
+        .elements
+        .iter()
+        .map(|(path, _)| gjson::get(crate::test_utils::SAMPLE_JSON, path).f64())
+        .collect();
+    assert_eq!(prices, vec![25.5, 1200.0, 75.0]);
+
+    // Negate applies to the aggregate only
+    let result = det.matches_detailed(&request("line_items.#.price", true)).unwrap();
+    assert!(!result.matched);
+    assert_eq!(result.elements[1], ("line_items.#.price|1".to_string(), true));
+
+    let result = det
+        .matches_detailed(&request("line_items.#(status==\"shipped\")#.price", false))
+        .unwrap();
+    assert_eq!(
+        result.elements.iter().map(|(_, matched)| *matched).collect::<Vec<bool>>(),
+        vec![false, true]
+    );
+    assert_eq!(
+        gjson::get(crate::test_utils::SAMPLE_JSON, &result.elements[1].0).f64(),
+        75.0
+    );
+
+    let result = det.matches_detailed(&request("line_items.1.price", false)).unwrap();
+    assert!(result.elements.is_empty());
+
+    let result = det.matches_detailed(&request("line_items_empty.#.price", false)).unwrap();
+    assert!(result.elements.is_empty());
+}
+
+#[test]
+fn test_parse_field_values() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let prices: Vec<f64> = crate::detective::parse_field_values(&data, "line_items.#.price")
+        .unwrap()
+        .iter()
+        .map(|json| gjson::parse(json).f64())
+        .collect();
+
+    assert_eq!(prices, vec![25.5, 1200.0, 75.0]);
+
+    // A plain path is a single value, even when it is an array
+    assert_eq!(crate::detective::parse_field_values(&data, "numbers").unwrap().len(), 1);
+    assert!(crate::detective::parse_field_values(&data, "does.not.#.exist").is_err());
+}
+
+#[test]
+fn test_supported_match_types() {
+    let supported = Detective::supported_match_types();
+
+    assert!(!supported.contains(&DetectiveType::DETECTIVE_TYPE_UNKNOWN));
+    assert!(!supported.contains(&DetectiveType::DETECTIVE_TYPE_PII_HEALTH));
+    assert!(supported.contains(&DetectiveType::DETECTIVE_TYPE_HAS_FIELD));
+    assert!(supported.contains(&DetectiveType::DETECTIVE_TYPE_PII_GEOLOCATION));
+    assert!(supported.contains(&DetectiveType::DETECTIVE_TYPE_PII_SSN));
+    assert!(supported.contains(&DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX));
+
+    let data = r#"{"field": "value"}"#.as_bytes().to_vec();
+    let det = Detective::new();
+
+    // Every type left out other than UNKNOWN is one whose matcher is a stub, and
+    // none of the supported ones are
+    for match_type in <DetectiveType as protobuf::Enum>::VALUES {
+        if *match_type == DetectiveType::DETECTIVE_TYPE_UNKNOWN {
+            continue;
+        }
+
+        let result = det.matches(&Request {
+            match_type: *match_type,
+            data: &data,
+            path: "field".to_string(),
+            args: vec![],
+            negate: false,
+        });
+
+        let stub = matches!(&result, Err(err) if err.to_string().contains("not implemented"));
+
+        assert_eq!(stub, !supported.contains(match_type), "{:?}", match_type);
+    }
+}
+
+#[test]
+fn test_matches_lenient() {
+    let det = Detective::new();
+    let data = r#"{"status": "active", "email": "not an email"}"#.as_bytes().to_vec();
+
+    let request = |match_type: DetectiveType, path: &str, args: Vec<&str>, negate: bool| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate,
+    };
+
+    let missing = request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "role", vec!["admin"], false);
+
+    assert!(matches!(det.matches(&missing), Err(CustomError::PathNotFound(path)) if path == "role"));
+    assert!(!det.matches_lenient(&missing).unwrap());
+
+    // Negate doesn't turn a missing path into a match
+    let negated = request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "role", vec!["admin"], true);
+    assert!(!det.matches_lenient(&negated).unwrap());
+
+    let present = request(DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "status", vec!["active"], false);
+    assert!(det.matches_lenient(&present).unwrap());
+
+    let is_empty = request(DetectiveType::DETECTIVE_TYPE_IS_EMPTY, "role", vec![], false);
+    assert!(matches!(det.matches(&is_empty), Err(CustomError::PathNotFound(_))));
+    assert!(!det.matches_lenient(&is_empty).unwrap());
+
+    // Other errors still surface
+    let bad_regex = request(DetectiveType::DETECTIVE_TYPE_REGEX, "status", vec!["(["], false);
+    assert!(det.matches_lenient(&bad_regex).is_err());
+}
+
+#[test]
+fn test_rule_set_tags() {
+    let rule = |label: &str, match_type: DetectiveType, path: &str, args: Vec<&str>| {
+        (
+            label.to_string(),
+            match_type,
+            path.to_string(),
+            args.into_iter().map(String::from).collect(),
+        )
+    };
+
+    let rules = RuleSet::new(vec![
+        rule("contains_pii", DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "", vec![]),
+        rule("high_value_txn", DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "amount", vec!["10000"]),
+        rule("refund", DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "type", vec!["refund"]),
+        // A second rule for a label that's already tagged
+        rule("contains_pii", DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD, "", vec![]),
+    ]);
+
+    let data = r#"{"type": "purchase", "amount": 25000, "customer": {"email": "jsmith@example.com"}}"#
+        .as_bytes()
+        .to_vec();
+
+    assert_eq!(rules.tags(&data).unwrap(), vec!["contains_pii", "high_value_txn"]);
+
+    // A missing path doesn't tag and doesn't error
+    let data = r#"{"type": "refund"}"#.as_bytes().to_vec();
+    assert_eq!(rules.tags(&data).unwrap(), vec!["refund"]);
+
+    let rules = RuleSet::new(vec![rule("bad", DetectiveType::DETECTIVE_TYPE_REGEX, "type", vec!["(["])]);
+    assert!(rules.tags(&data).is_err());
+}
+
+#[test]
+fn test_field_arg() {
+    let det = Detective::new_strict();
+    let data = r#"{
+        "order": {"total": 120, "paid": 120, "refunded": 20, "currency": "EUR"},
+        "invoice": {"total": "120", "currency": "EUR"}
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let request = |match_type: DetectiveType, path: &str, other: &str| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: vec![other.to_string(), FIELD_ARG.to_string()],
+        negate: false,
+    };
+
+    let test_cases = vec![
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "order.total", "order.paid", true),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "order.total", "order.refunded", true),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN, "order.total", "order.refunded", false),
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "order.currency", "invoice.currency", true),
+    ];
+
+    for (match_type, path, other, expected) in test_cases {
+        assert_eq!(
+            det.matches(&request(match_type, path, other)).unwrap(),
+            expected,
+            "{:?} {} {}",
+            match_type,
+            path,
+            other
+        );
+    }
+
+    // A number and a numeric string are a type mismatch, not a silent false
+    let result = det.matches(&request(
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+        "order.total",
+        "invoice.total",
+    ));
+    assert!(matches!(result, Err(CustomError::InvalidField(_))), "{:?}", result);
+
+    let result = det.matches(&request(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "order.currency",
+        "order.missing",
+    ));
+    assert!(matches!(result, Err(CustomError::PathNotFound(_))), "{:?}", result);
+
+    let result = det.matches(&request(
+        DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY,
+        "order.currency",
+        "invoice.currency",
+    ));
+    assert!(matches!(result, Err(CustomError::InvalidArgument(_))), "{:?}", result);
+}
+
+#[test]
+fn test_matches_many_within() {
+    use crate::detective::evaluate_within;
+    use std::time::Duration;
+
+    let det = Detective::new();
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let request = |path: &str| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+        data: &data,
+        path: path.to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    let requests = vec![
+        request("object.field"),
+        request("does.not.exist"),
+        request("number_int"),
+    ];
+
+    let batch = det.matches_many_within(&requests, Duration::from_secs(60));
+    assert!(batch.complete);
+    assert_eq!(
+        batch.results.into_iter().map(Result::unwrap).collect::<Vec<bool>>(),
+        vec![true, false, true]
+    );
+
+    let batch = det.matches_many_within(&requests, Duration::ZERO);
+    assert!(!batch.complete);
+    assert!(batch.results.is_empty());
+
+    // A slow matcher uses up the budget, so the requests after it are skipped
+    let batch = evaluate_within(&requests, Duration::from_millis(50), |request| {
+        std::thread::sleep(Duration::from_millis(100));
+        det.matches(request)
+    });
+    assert!(!batch.complete);
+    assert_eq!(batch.results.len(), 1);
+    assert!(batch.results[0].as_ref().unwrap());
+}
+
+#[test]
+fn test_matches_json_value() {
+    let det = Detective::new();
+    let data = r#"{
+        "boolean_t": true,
+        "numbers": [1, 2, 3, 4, 500],
+        "number_int": 100,
+        "number_float": 100.1,
+        "object": {
+            "field": "value",
+            "null_field": null,
+            "empty_array": [],
+            "ipv4_address": "127.0.0.1"
+        }
+    }"#
+    .as_bytes()
+    .to_vec();
+    let value: serde_json::Value = serde_json::from_slice(&data).unwrap();
+
+    let test_cases = vec![
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "object.field", vec!["value"], false),
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "object.field", vec!["other"], true),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "number_int", vec!["99"], false),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "number_float", vec!["100.1"], false),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "numbers.4", vec!["500"], false),
+        (DetectiveType::DETECTIVE_TYPE_BOOLEAN_TRUE, "boolean_t", vec![], false),
+        (DetectiveType::DETECTIVE_TYPE_IS_EMPTY, "object.empty_array", vec![], false),
+        (DetectiveType::DETECTIVE_TYPE_IS_EMPTY, "object.missing", vec!["missing_is_empty"], false),
+        (DetectiveType::DETECTIVE_TYPE_HAS_FIELD, "object.missing", vec![], false),
+        (DetectiveType::DETECTIVE_TYPE_HAS_FIELD, "object.null_field", vec![], true),
+        (DetectiveType::DETECTIVE_TYPE_IPV4_ADDRESS, "object.ipv4_address", vec![], false),
+        (DetectiveType::DETECTIVE_TYPE_STRING_LENGTH_MIN, "object.field", vec!["3"], false),
+        // Not plain paths, resolved by serializing the value
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "numbers.#", vec!["5"], false),
+        (DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "numbers[1:3]", vec!["2"], false),
+        (DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY, "", vec!["value"], false),
+        (DetectiveType::DETECTIVE_TYPE_STRING_EQUAL, "object.field", vec!["VALUE", "transform:lowercase"], false),
+    ];
+
+    for (match_type, path, args, negate) in test_cases {
+        let request = Request {
+            match_type,
+            data: &data,
+            path: path.to_string(),
+            args: args.into_iter().map(String::from).collect(),
+            negate,
+        };
+
+        assert_eq!(
+            det.matches_json_value(&request, &value).unwrap(),
+            det.matches(&request).unwrap(),
+            "{:?} {}",
+            match_type,
+            path
+        );
+    }
+
+    // request.data isn't used, and missing paths error as with matches()
+    let empty = Vec::new();
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        data: &empty,
+        path: "object.missing".to_string(),
+        args: vec!["value".to_string()],
+        negate: false,
+    };
+    assert!(matches!(
+        det.matches_json_value(&request, &value),
+        Err(CustomError::PathNotFound(_))
+    ));
+
+    assert_eq!(parse_field_from_value::<f64>(&value, "numbers.1").unwrap(), 2.0);
+    assert_eq!(parse_field_from_value::<f64>(&value, "numbers.#").unwrap(), 5.0);
+    assert!(parse_field_from_value::<bool>(&value, "boolean_t").unwrap());
+    assert_eq!(
+        parse_field_from_value::<String>(&value, "object.field").unwrap(),
+        "value"
+    );
+    assert!(parse_field_from_value::<f64>(&value, "object.field").is_err());
+    assert!(matches!(
+        parse_field_from_value::<f64>(&value, "numbers.9"),
+        Err(CustomError::PathNotFound(_))
+    ));
+}
+
+#[test]
+fn test_extract_as() {
+    let det = Detective::new();
+    let data = r#"{
+        "count": 42,
+        "negative": -1,
+        "ratio": 0.5,
+        "enabled": true,
+        "id": "42",
+        "big": 1e3
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let request = |path: &str| Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        data: &data,
+        path: path.to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    assert_eq!(det.extract_as::<i64>(&request("count")).unwrap(), 42);
+    assert_eq!(det.extract_as::<u64>(&request("count")).unwrap(), 42);
+    assert_eq!(det.extract_as::<f64>(&request("ratio")).unwrap(), 0.5);
+    assert!(det.extract_as::<bool>(&request("enabled")).unwrap());
+    assert_eq!(det.extract_as::<String>(&request("id")).unwrap(), "42");
+
+    // Numbers aren't parsed out of strings or truncated
+    let err = det.extract_as::<i64>(&request("id")).unwrap_err();
+    assert!(matches!(err, CustomError::InvalidField(_)), "{:?}", err);
+    assert_eq!(
+        err.to_string(),
+        "invalid field value: not a number at 'id', found string"
+    );
+
+    let err = det.extract_as::<bool>(&request("count")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid field value: not a boolean at 'count', found number"
+    );
+
+    let err = det.extract_as::<u64>(&request("negative")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid field value: not an unsigned integer at 'negative', found number"
+    );
+
+    assert!(matches!(
+        det.extract_as::<i64>(&request("ratio")),
+        Err(CustomError::InvalidField(_))
+    ));
+    assert!(matches!(
+        det.extract_as::<i64>(&request("big")),
+        Err(CustomError::InvalidField(_))
+    ));
+    assert!(matches!(
+        det.extract_as::<i64>(&request("missing")),
+        Err(CustomError::PathNotFound(_))
+    ));
+}
+
+#[test]
+fn test_matches_detailed_card_brand() {
+    let det = Detective::new();
+    let data = r#"{
+        "visa": "4539 1488 0343 6467",
+        "amex": "378734493671000",
+        "id": "1234567812345678"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let detailed = |match_type: DetectiveType, path: &str| {
+        det.matches_detailed(&Request {
+            match_type,
+            data: &data,
+            path: path.to_string(),
+            args: vec![],
+            negate: false,
+        })
+        .unwrap()
+    };
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD, "visa");
+    assert!(result.matched);
+    assert_eq!(result.card_brand, Some("visa"));
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_ANY, "amex");
+    assert_eq!(result.detected_type, Some(DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD));
+    assert_eq!(result.card_brand, Some("amex"));
+
+    // No brand unless the number was detected as a card
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD, "id");
+    assert!(!result.matched);
+    assert_eq!(result.card_brand, None);
+
+    let result = detailed(DetectiveType::DETECTIVE_TYPE_HAS_FIELD, "visa");
+    assert_eq!(result.card_brand, None);
+}
+
+#[test]
+fn test_pattern_overrides() {
+    let mut det = Detective::new();
+    let data = r#"{"contact": "jane@internal", "other": "jane@example.org"}"#
+        .as_bytes()
+        .to_vec();
+
+    let request = |match_type: DetectiveType, path: &str| Request {
+        match_type,
+        data: &data,
+        path: path.to_string(),
+        args: vec![],
+        negate: false,
+    };
+
+    // The built-in check wants a dotted domain
+    assert!(!det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "contact")).unwrap());
+
+    det.set_pattern_overrides(HashMap::from([(
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+        r"^[a-z]+@internal$".to_string(),
+    )]))
+    .unwrap();
+
+    assert!(det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "contact")).unwrap());
+    assert!(!det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "other")).unwrap());
+    assert!(det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_ANY, "contact")).unwrap());
+    assert!(det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "")).unwrap());
+    assert_eq!(
+        det.pii_summary(&data).unwrap().get(&DetectiveType::DETECTIVE_TYPE_PII_EMAIL),
+        Some(&1)
+    );
+
+    // Other detectives keep the built-in check
+    assert!(Detective::new()
+        .matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "other"))
+        .unwrap());
+
+    // Bad patterns and types PII_ANY doesn't consult are rejected up front,
+    // leaving the current overrides in place
+    let result = det.set_pattern_overrides(HashMap::from([(
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+        "([a-z".to_string(),
+    )]));
+    assert!(matches!(result, Err(CustomError::RegexError { .. })), "{:?}", result);
+
+    let result = det.set_pattern_overrides(HashMap::from([(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "x".to_string(),
+    )]));
+    assert!(matches!(result, Err(CustomError::InvalidArgument(_))), "{:?}", result);
+
+    let result = det.set_pattern_overrides(HashMap::from([(
+        DetectiveType::DETECTIVE_TYPE_PII_PHONE,
+        "x".to_string(),
+    )]));
+    assert!(
+        matches!(result, Err(CustomError::InvalidArgument(ref msg)) if msg.contains("DETECTIVE_TYPE_PII_SSN")),
+        "{:?}",
+        result
+    );
+
+    assert!(det.matches(&request(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, "contact")).unwrap());
+}
+
+#[test]
+fn test_detective_send_sync() {
+    fn _assert_send_sync<T: Send + Sync>() {}
+    _assert_send_sync::<Detective>();
+
+    let det = std::sync::Arc::new(Detective::new());
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let det = std::sync::Arc::clone(&det);
+
+            std::thread::spawn(move || {
+                for j in 0..50 {
+                    let data = format!(r#"{{"id": "user-{}-{}", "other": "nope"}}"#, i, j)
+                        .into_bytes();
+
+                    let request = |path: &str| Request {
+                        match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                        data: &data,
+                        path: path.to_string(),
+                        args: vec![r"^user-\d+-\d+$".to_string()],
+                        negate: false,
+                    };
+
+                    assert!(det.matches(&request("id")).unwrap());
+                    assert!(!det.matches(&request("other")).unwrap());
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
@@ -14,6 +14,28 @@ pub enum CustomError {
         source: regex::Error,
     },
 
+    /// A rule arg that can't be used, ie. a non-numeric bound. Fix the rule.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// The request path doesn't resolve to a value in the payload. See
+    /// `Detective::matches_lenient()` to treat this as not matching.
+    #[error("path '{0}' not found in data")]
+    PathNotFound(String),
+
+    /// A field value the matcher can't work with, ie. a non-numeric string. Fix the data.
+    #[error("invalid field value: {0}")]
+    InvalidField(String),
+
+    /// An error from a leaf of a combinator tree, with the route to that leaf,
+    /// ie. `and[1] > not > string_equal on 'status'`.
+    #[error("{breadcrumb}: {source}")]
+    InTree {
+        breadcrumb: String,
+        #[source]
+        source: Box<CustomError>,
+    },
+
     #[error("missing match type: {0}")]
     MissingMatchType(i32),
 }
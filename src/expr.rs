@@ -0,0 +1,358 @@
+use crate::detective;
+use crate::error::CustomError;
+use protobuf::Enum;
+use protos::sp_steps_detective::DetectiveType;
+
+/// A tree of match requests combined with boolean logic. Leaves carry
+/// everything a `Request` needs except the data, which is supplied at
+/// evaluation time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Match {
+        match_type: DetectiveType,
+        path: String,
+        args: Vec<String>,
+    },
+    /// A standalone matcher without a `DetectiveType`, by its name in
+    /// `Detective::supported_matcher_names()`.
+    Named {
+        name: String,
+        path: String,
+        args: Vec<String>,
+    },
+}
+
+/// A matcher as named in an expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Matcher {
+    Type(DetectiveType),
+    Named(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Op(&'static str),
+    Word(String),
+    Quoted(String),
+}
+
+const OPERATORS: [&str; 6] = [">=", "<=", "==", "!=", ">", "<"];
+
+/// Parse a rule expression, for example:
+///
+/// ```text
+/// age > 18 && email is_pii_email
+/// !(status == "deleted" || tags contains_any "spam" "abuse")
+/// ```
+///
+/// A comparison is `<path> <op> <value>` where op is one of `== != > >= < <=`.
+/// Unquoted numeric values compare numerically, anything else as a string.
+/// Otherwise a comparison is `<path> <matcher> [args...]` where matcher is a
+/// `DetectiveType` name in lowercase without the `DETECTIVE_TYPE_` prefix,
+/// optionally prefixed with `is_` (ie. `pii_email`, `is_pii_email`, `string_contains_any`).
+/// `contains_any`/`contains_all` are accepted as shorthand for the string variants.
+/// Standalone matchers are named as in `Detective::supported_matcher_names()`
+/// (ie. `glob`, `is_base32`), with the same optional `is_` prefix.
+///
+/// `!` binds tighter than `&&`, which binds tighter than `||`.
+pub fn parse(expr: &str) -> Result<Expr, CustomError> {
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser { tokens, pos: 0 };
+
+    let tree = parser.or()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(CustomError::Error(format!(
+            "unexpected token in expression: {:?}",
+            token
+        )));
+    }
+
+    Ok(tree)
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, CustomError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        c => value.push(c),
+                    }
+                }
+
+                if !closed {
+                    return Err(CustomError::Error(
+                        "unterminated string in expression".to_string(),
+                    ));
+                }
+
+                tokens.push(Token::Quoted(value));
+            }
+            _ => {
+                let mut word = String::new();
+
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+
+                    word.push(c);
+                    chars.next();
+                }
+
+                tokens.append(&mut split_word(&word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// A run of non-whitespace can hold several tokens, ie. "!has_field" or "age>18"
+fn split_word(word: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut rest = word;
+
+    let flush = |current: &mut String, tokens: &mut Vec<Token>| {
+        if !current.is_empty() {
+            tokens.push(Token::Word(std::mem::take(current)));
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(stripped) = rest.strip_prefix("&&") {
+            flush(&mut current, &mut tokens);
+            tokens.push(Token::And);
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("||") {
+            flush(&mut current, &mut tokens);
+            tokens.push(Token::Or);
+            rest = stripped;
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            flush(&mut current, &mut tokens);
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c == '!' {
+            flush(&mut current, &mut tokens);
+            tokens.push(Token::Not);
+            rest = &rest[1..];
+        } else {
+            current.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    flush(&mut current, &mut tokens);
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, CustomError> {
+        let mut left = self.and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, CustomError> {
+        let mut left = self.unary()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CustomError> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::Open) => {
+                self.next();
+                let inner = self.or()?;
+
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(CustomError::Error(
+                        "missing closing parenthesis in expression".to_string(),
+                    )),
+                }
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, CustomError> {
+        let path = match self.next() {
+            Some(Token::Word(path)) | Some(Token::Quoted(path)) => path,
+            token => {
+                return Err(CustomError::Error(format!(
+                    "expected a path in expression, got {:?}",
+                    token
+                )))
+            }
+        };
+
+        match self.next() {
+            Some(Token::Op(op)) => {
+                let (value, quoted) = match self.next() {
+                    Some(Token::Word(value)) => (value, false),
+                    Some(Token::Quoted(value)) => (value, true),
+                    token => {
+                        return Err(CustomError::Error(format!(
+                            "expected a value after '{}' in expression, got {:?}",
+                            op, token
+                        )))
+                    }
+                };
+
+                comparison_expr(path, op, value, quoted)
+            }
+            Some(Token::Word(matcher)) => {
+                let matcher = resolve_matcher(&matcher)?;
+                let mut args = Vec::new();
+
+                while let Some(Token::Word(_)) | Some(Token::Quoted(_)) = self.peek() {
+                    match self.next() {
+                        Some(Token::Word(arg)) | Some(Token::Quoted(arg)) => args.push(arg),
+                        _ => unreachable!("peeked an arg token"),
+                    }
+                }
+
+                Ok(match matcher {
+                    Matcher::Type(match_type) => Expr::Match {
+                        match_type,
+                        path,
+                        args,
+                    },
+                    Matcher::Named(name) => Expr::Named {
+                        name: name.to_string(),
+                        path,
+                        args,
+                    },
+                })
+            }
+            token => Err(CustomError::Error(format!(
+                "expected an operator or matcher after '{}' in expression, got {:?}",
+                path, token
+            ))),
+        }
+    }
+}
+
+fn comparison_expr(path: String, op: &str, value: String, quoted: bool) -> Result<Expr, CustomError> {
+    let numeric = !quoted && value.parse::<f64>().is_ok();
+
+    let match_type = match (op, numeric) {
+        ("==" | "!=", false) => DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        ("==" | "!=", true) => DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+        (">", true) => DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+        (">=", true) => DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL,
+        ("<", true) => DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN,
+        ("<=", true) => DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL,
+        _ => {
+            return Err(CustomError::Error(format!(
+                "operator '{}' requires a numeric value, got '{}'",
+                op, value
+            )))
+        }
+    };
+
+    let leaf = Expr::Match {
+        match_type,
+        path,
+        args: vec![value],
+    };
+
+    if op == "!=" {
+        return Ok(Expr::Not(Box::new(leaf)));
+    }
+
+    Ok(leaf)
+}
+
+/// Resolve a matcher name as used in expressions, ie. `numeric_greater_than`,
+/// `is_pii_email` or `glob`.
+pub(crate) fn resolve_matcher(name: &str) -> Result<Matcher, CustomError> {
+    let lookup = |name: &str| {
+        let type_name = match name {
+            "contains_any" => "string_contains_any",
+            "contains_all" => "string_contains_all",
+            name => name,
+        };
+
+        match DetectiveType::from_str(&format!("DETECTIVE_TYPE_{}", type_name.to_ascii_uppercase())) {
+            Some(DetectiveType::DETECTIVE_TYPE_UNKNOWN) | None => {
+                detective::named_matcher(name).map(|(name, _)| Matcher::Named(name))
+            }
+            Some(match_type) => Some(Matcher::Type(match_type)),
+        }
+    };
+
+    // "is_empty" and "is_regex" are names as-is, "is_pii_email" only without the
+    // prefix. An exact name always wins over one with the prefix stripped.
+    lookup(name)
+        .or_else(|| name.strip_prefix("is_").and_then(lookup))
+        .ok_or_else(|| CustomError::MatchError(format!("unknown matcher in expression: {}", name)))
+}
@@ -0,0 +1,147 @@
+use crate::detective::Detective;
+use crate::expr::{parse, resolve_matcher, Expr, Matcher};
+#[cfg(test)]
+use protos::sp_steps_detective::DetectiveType;
+
+const SAMPLE_USER: &str = r#"{
+    "age": 21,
+    "name": "bob smith",
+    "email": "bob@streamdal.com",
+    "status": "active",
+    "tags": ["admin", "beta"]
+}"#;
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("age > 18 && email is_pii_email").unwrap(),
+        Expr::And(
+            Box::new(Expr::Match {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                path: "age".to_string(),
+                args: vec!["18".to_string()],
+            }),
+            Box::new(Expr::Match {
+                match_type: DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+                path: "email".to_string(),
+                args: vec![],
+            }),
+        )
+    );
+
+    assert_eq!(
+        parse(r#"!(status=="deleted")"#).unwrap(),
+        Expr::Not(Box::new(Expr::Match {
+            match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            path: "status".to_string(),
+            args: vec!["deleted".to_string()],
+        }))
+    );
+
+    assert_eq!(
+        parse(r#"host glob "*.streamdal.com" && !key is_base32"#).unwrap(),
+        Expr::And(
+            Box::new(Expr::Named {
+                name: "glob".to_string(),
+                path: "host".to_string(),
+                args: vec!["*.streamdal.com".to_string()],
+            }),
+            Box::new(Expr::Not(Box::new(Expr::Named {
+                name: "is_base32".to_string(),
+                path: "key".to_string(),
+                args: vec![],
+            }))),
+        )
+    );
+
+    // The is_ prefix is optional for named matchers too
+    assert_eq!(
+        parse("name is_distinct_chars gt 3").unwrap(),
+        Expr::Named {
+            name: "distinct_chars".to_string(),
+            path: "name".to_string(),
+            args: vec!["gt".to_string(), "3".to_string()],
+        }
+    );
+
+    assert!(parse("age >").is_err());
+    assert!(parse("age > eighteen").is_err());
+    assert!(parse("age is_bogus").is_err());
+    assert!(parse("(age > 18").is_err());
+    assert!(parse(r#"name == "unterminated"#).is_err());
+}
+
+#[test]
+fn test_matches_expr() {
+    let data = SAMPLE_USER.as_bytes();
+    let det = Detective::new();
+
+    let test_cases = vec![
+        ("age > 18 && email is_pii_email", true),
+        ("age > 30 && email is_pii_email", false),
+        ("age > 30 || email is_pii_email", true),
+        (r#"!(status == "deleted")"#, true),
+        (r#"status != "active""#, false),
+        ("!status has_field", false),
+        (r#"name contains_all "bob" "smith" && !tags is_empty"#, true),
+        (r#"age >= 21 && age <= 21 && name == "bob smith""#, true),
+        (r#"status glob "act*" && name distinct_chars gt 5"#, true),
+        ("name distinct_chars lt 5 || email is_base32", false),
+        // is_regex is the named matcher, not REGEX with the prefix stripped
+        (r#"email is_regex && !email regex "^x""#, true),
+    ];
+
+    for (expr, expected) in test_cases {
+        assert_eq!(det.matches_expr(expr, data).unwrap(), expected, "{}", expr);
+    }
+
+    // Errors from a matcher are not swallowed by negation
+    assert!(det.matches_expr("!does_not_exist == 1", data).is_err());
+}
+
+#[test]
+fn test_resolve_matcher_named() {
+    // A named matcher sharing a DetectiveType's name would never be reached
+    for name in Detective::supported_matcher_names() {
+        assert_eq!(resolve_matcher(name).unwrap(), Matcher::Named(name), "{}", name);
+    }
+}
+
+#[test]
+fn test_matches_expr_error_breadcrumb() {
+    use crate::error::CustomError;
+
+    let data = SAMPLE_USER.as_bytes();
+    let det = Detective::new();
+
+    let err = det
+        .matches_expr(
+            r#"age > 18 && (status == "deleted" || !missing.field == 1)"#,
+            data,
+        )
+        .unwrap_err();
+
+    match &err {
+        CustomError::InTree { breadcrumb, source } => {
+            assert_eq!(breadcrumb, "and[1] > or[1] > not > numeric_equal_to on 'missing.field'");
+            assert!(source.to_string().contains("missing.field"), "{}", source);
+        }
+        err => panic!("expected an InTree error, got {:?}", err),
+    }
+
+    assert!(err.to_string().starts_with("and[1] > or[1]"), "{}", err);
+
+    // A failing root leaf has just the leaf as the breadcrumb
+    let err = det.matches_expr("name > 1", data).unwrap_err();
+    assert!(matches!(
+        err,
+        CustomError::InTree { ref breadcrumb, .. } if breadcrumb == "numeric_greater_than on 'name'"
+    ), "{:?}", err);
+
+    // Named matchers are reported by their registered name
+    let err = det.matches_expr("age > 18 && name glob", data).unwrap_err();
+    assert!(matches!(
+        err,
+        CustomError::InTree { ref breadcrumb, .. } if breadcrumb == "and[1] > glob on 'name'"
+    ), "{:?}", err);
+}
@@ -0,0 +1,106 @@
+//! C ABI entry point for hosts that embed the detective outside of Rust.
+//! Enabled with the `ffi` feature.
+//!
+//! The match request is a serialized `protos.steps.DetectiveStep` message.
+use crate::detective::{Detective, Request};
+use crate::error::CustomError;
+use protobuf::Message;
+use protos::sp_steps_detective::DetectiveStep;
+use std::ffi::{c_char, CString};
+use std::panic;
+use std::ptr;
+use std::slice;
+
+/// The data matched the request
+pub const DETECTIVE_MATCH: i32 = 1;
+/// The data did not match the request
+pub const DETECTIVE_NO_MATCH: i32 = 0;
+/// The request could not be evaluated, see the error string
+pub const DETECTIVE_ERROR: i32 = -1;
+
+/// Evaluate a serialized `DetectiveStep` against `data`. Returns one of
+/// `DETECTIVE_MATCH`, `DETECTIVE_NO_MATCH` or `DETECTIVE_ERROR`.
+///
+/// On error, if `error_out` is not null it is set to a NUL-terminated message
+/// that must be released with `detective_free_error()`. It is set to null otherwise.
+///
+/// # Safety
+///
+/// `step_ptr` and `data_ptr` must point to at least `step_len` and `data_len`
+/// readable bytes; either may be null only if its length is 0. `error_out`
+/// must be null or point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn detective_matches(
+    step_ptr: *const u8,
+    step_len: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    error_out: *mut *mut c_char,
+) -> i32 {
+    if !error_out.is_null() {
+        *error_out = ptr::null_mut();
+    }
+
+    let result = panic::catch_unwind(|| {
+        let step = as_slice(step_ptr, step_len, "step")?;
+        let data = as_slice(data_ptr, data_len, "data")?.to_vec();
+
+        matches_step(step, &data)
+    })
+    .unwrap_or_else(|_| Err(CustomError::Error("panic while matching".to_string())));
+
+    match result {
+        Ok(true) => DETECTIVE_MATCH,
+        Ok(false) => DETECTIVE_NO_MATCH,
+        Err(err) => {
+            if !error_out.is_null() {
+                // Interior NULs would truncate the message on the C side anyway
+                let message = err.to_string().replace('\0', "");
+                *error_out = CString::new(message).unwrap_or_default().into_raw();
+            }
+
+            DETECTIVE_ERROR
+        }
+    }
+}
+
+/// Release an error string returned by `detective_matches()`. Null is ignored.
+///
+/// # Safety
+///
+/// `error` must be null or a pointer returned via `detective_matches()` that
+/// hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn detective_free_error(error: *mut c_char) {
+    if !error.is_null() {
+        drop(CString::from_raw(error));
+    }
+}
+
+unsafe fn as_slice<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], CustomError> {
+    match (ptr.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(CustomError::Error(format!(
+            "{} is null but has a length of {}",
+            name, len
+        ))),
+        (false, len) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+fn matches_step(step: &[u8], data: &Vec<u8>) -> Result<bool, CustomError> {
+    let step = DetectiveStep::parse_from_bytes(step)
+        .map_err(|e| CustomError::Error(format!("unable to decode detective step: {}", e)))?;
+
+    let match_type = step.type_.enum_value().map_err(CustomError::MissingMatchType)?;
+
+    let request = Request {
+        match_type,
+        data,
+        path: step.path.unwrap_or_default(),
+        args: step.args,
+        negate: step.negate.unwrap_or_default(),
+    };
+
+    Detective::new().matches(&request)
+}
@@ -0,0 +1,89 @@
+use crate::ffi::{
+    detective_free_error, detective_matches, DETECTIVE_ERROR, DETECTIVE_MATCH, DETECTIVE_NO_MATCH,
+};
+use protobuf::{EnumOrUnknown, Message};
+use protos::sp_steps_detective::{DetectiveStep, DetectiveType};
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+fn step(match_type: DetectiveType, path: &str, args: Vec<&str>) -> Vec<u8> {
+    let mut step = DetectiveStep::new();
+    step.type_ = EnumOrUnknown::new(match_type);
+    step.path = Some(path.to_string());
+    step.args = args.into_iter().map(String::from).collect();
+
+    step.write_to_bytes().unwrap()
+}
+
+// Call through the C ABI, returning the result code and error message (if any)
+fn call(step: &[u8], data: &[u8]) -> (i32, Option<String>) {
+    let mut error: *mut c_char = ptr::null_mut();
+
+    let code = unsafe {
+        detective_matches(step.as_ptr(), step.len(), data.as_ptr(), data.len(), &mut error)
+    };
+
+    let message = if error.is_null() {
+        None
+    } else {
+        let message = unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned();
+        unsafe { detective_free_error(error) };
+        Some(message)
+    };
+
+    (code, message)
+}
+
+#[test]
+fn test_ffi_matches() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes();
+
+    let matching = step(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "object.field",
+        vec!["value"],
+    );
+    assert_eq!(call(&matching, data), (DETECTIVE_MATCH, None));
+
+    let not_matching = step(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "object.field",
+        vec!["other"],
+    );
+    assert_eq!(call(&not_matching, data), (DETECTIVE_NO_MATCH, None));
+}
+
+#[test]
+fn test_ffi_errors() {
+    let data = crate::test_utils::SAMPLE_JSON.as_bytes();
+
+    let missing_path = step(
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+        "does.not.exist",
+        vec!["value"],
+    );
+    let (code, message) = call(&missing_path, data);
+    assert_eq!(code, DETECTIVE_ERROR);
+    assert!(message.unwrap().contains("does.not.exist"));
+
+    let (code, message) = call(b"\xff\xff\xff", data);
+    assert_eq!(code, DETECTIVE_ERROR);
+    assert!(message.unwrap().contains("unable to decode"));
+
+    // Empty data is valid at the boundary but rejected by the detective
+    let matching = step(DetectiveType::DETECTIVE_TYPE_HAS_FIELD, "object", vec![]);
+    let (code, message) = call(&matching, &[]);
+    assert_eq!(code, DETECTIVE_ERROR);
+    assert!(message.is_some());
+
+    // Null pointers with a length, and a null error_out
+    let code = unsafe { detective_matches(ptr::null(), 10, data.as_ptr(), data.len(), ptr::null_mut()) };
+    assert_eq!(code, DETECTIVE_ERROR);
+
+    let code = unsafe {
+        detective_matches(matching.as_ptr(), matching.len(), ptr::null(), 0, ptr::null_mut())
+    };
+    assert_eq!(code, DETECTIVE_ERROR);
+
+    unsafe { detective_free_error(ptr::null_mut()) };
+}
@@ -0,0 +1,77 @@
+use crate::hash::{keccak256, sha256};
+
+fn hex(digest: [u8; 32]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_sha256() {
+    let test_cases = vec![
+        ("", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        ("abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        assert_eq!(hex(sha256(input.as_bytes())), expected, "{}", input);
+    }
+
+    // Padding spills into a second block
+    let long = "a".repeat(1000);
+    assert_eq!(
+        hex(sha256(long.as_bytes())),
+        "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+    );
+
+    // Either side of where the 0x80 byte and length stop fitting in one block,
+    // and the one million "a" vector from FIPS 180-2
+    let padding_cases = vec![
+        (55, "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318"),
+        (56, "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"),
+        (63, "7d3e74a05d7db15bce4ad9ec0658ea98e3f06eeecf16b4c6fff2da457ddc2f34"),
+        (64, "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"),
+        (1_000_000, "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"),
+    ];
+
+    for (len, expected) in padding_cases {
+        assert_eq!(hex(sha256("a".repeat(len).as_bytes())), expected, "{} bytes", len);
+    }
+}
+
+#[test]
+fn test_keccak256() {
+    let test_cases = vec![
+        ("", "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+        ("abc", "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"),
+    ];
+
+    for (input, expected) in test_cases {
+        assert_eq!(hex(keccak256(input.as_bytes())), expected, "{}", input);
+    }
+
+    // Exactly one 136 byte rate block (padding needs a block of its own), and
+    // input absorbed over two blocks
+    assert_eq!(
+        hex(keccak256("a".repeat(136).as_bytes())),
+        "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e"
+    );
+    assert_eq!(
+        hex(keccak256("a".repeat(200).as_bytes())),
+        "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d"
+    );
+
+    // One byte short of and past a rate block, where the 0x01 and 0x80 padding
+    // bits share a byte or not, and exactly two blocks
+    let padding_cases = vec![
+        (135, "34367dc248bbd832f4e3e69dfaac2f92638bd0bbd18f2912ba4ef454919cf446"),
+        (137, "d869f639c7046b4929fc92a4d988a8b22c55fbadb802c0c66ebcd484f1915f39"),
+        (272, "cf7fcd4f705ee749930d19ca84561a9bf62516bd90a471545fa2f49fdc7e63c8"),
+    ];
+
+    for (len, expected) in padding_cases {
+        assert_eq!(hex(keccak256("a".repeat(len).as_bytes())), expected, "{} bytes", len);
+    }
+}
@@ -1,12 +1,38 @@
-#![feature(test)]
+#![cfg_attr(test, feature(test))]
 use crate::error::CustomError;
 use gjson::Value;
 
+pub mod data_format;
 pub mod detective;
 pub mod error;
+pub mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod hash;
 pub mod matcher_core;
 pub mod matcher_numeric;
 pub mod matcher_pii;
+pub mod transform;
+
+#[cfg(test)]
+#[path = "data_format_tests.rs"]
+mod data_format_tests;
+
+#[cfg(test)]
+#[path = "detective_tests.rs"]
+mod detective_tests;
+
+#[cfg(test)]
+#[path = "expr_tests.rs"]
+mod expr_tests;
+
+#[cfg(all(test, feature = "ffi"))]
+#[path = "ffi_tests.rs"]
+mod ffi_tests;
+
+#[cfg(test)]
+#[path = "hash_tests.rs"]
+mod hash_tests;
 
 #[cfg(test)]
 #[path = "matcher_numeric_tests.rs"]
@@ -28,6 +54,10 @@ mod test_utils;
 #[path = "test_bench.rs"]
 mod test_bench;
 
+#[cfg(test)]
+#[path = "transform_tests.rs"]
+mod transform_tests;
+
 pub trait FromValue<'a>
 where
     Self: Sized,
@@ -55,6 +85,34 @@ impl FromValue<'_> for f64 {
     }
 }
 
+// Integers must be written as such in the JSON, so 1.5 and 1e3 are rejected
+// rather than truncated
+impl FromValue<'_> for i64 {
+    fn from_value(value: Value) -> Result<Self, CustomError> {
+        if value.kind() != gjson::Kind::Number {
+            return Err(CustomError::Error("not a number".to_string()));
+        }
+
+        value
+            .json()
+            .parse()
+            .map_err(|_| CustomError::Error("not an integer".to_string()))
+    }
+}
+
+impl FromValue<'_> for u64 {
+    fn from_value(value: Value) -> Result<Self, CustomError> {
+        if value.kind() != gjson::Kind::Number {
+            return Err(CustomError::Error("not a number".to_string()));
+        }
+
+        value
+            .json()
+            .parse()
+            .map_err(|_| CustomError::Error("not an unsigned integer".to_string()))
+    }
+}
+
 impl<'a> FromValue<'a> for Value<'a> {
     fn from_value(value: Value<'a>) -> Result<Value<'a>, CustomError> {
         Ok(value)
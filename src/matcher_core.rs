@@ -1,34 +1,72 @@
 use crate::error::CustomError;
+use base64::Engine;
 use chrono::TimeZone;
-use crate::detective::{parse_number, Request};
+use crate::detective::{compare, named_matcher, parse_number, Detective, MatcherFunc, Request};
+use crate::expr::Matcher;
+use crate::matcher_pii as pii;
 use gjson::Value;
+use lazy_static::lazy_static;
 use protos::sp_steps_detective::DetectiveType;
-use regex::Regex;
+use regex::{Regex, RegexSet};
+use std::collections::HashMap;
 use std::net::IpAddr;
 use std::str;
 use std::str::FromStr;
+use std::sync::Mutex;
+use unicode_normalization::{is_nfc, is_nfd, is_nfkc, is_nfkd};
+use unicode_script::{Script, UnicodeScript};
 use url::Url;
 use uuid::Uuid;
 
+/// Option arg for the string equal/contains matchers: compare ASCII
+/// case-insensitively, ie. "Example.COM" equals "example.com".
+pub const IGNORE_CASE: &str = "ignore_case";
+
+// Split IGNORE_CASE off the args, lowercasing the field and values when it's set
+fn case_folded(request: &Request, field: &Value) -> (String, Vec<String>) {
+    let ignore_case = request.args.iter().any(|a| a == IGNORE_CASE);
+
+    let fold = |s: &str| {
+        if ignore_case {
+            s.to_ascii_lowercase()
+        } else {
+            s.to_string()
+        }
+    };
+
+    let args = request
+        .args
+        .iter()
+        .filter(|a| a.as_str() != IGNORE_CASE)
+        .map(|a| fold(a))
+        .collect();
+
+    (fold(field.str()), args)
+}
+
 pub fn string_equal_to(request: &Request, field: Value) -> Result<bool, CustomError> {
-    if request.args.len() != 1 {
+    let (field, args) = case_folded(request, &field);
+
+    if args.len() != 1 {
         return Err(CustomError::Error(
             "string_equal_to requires exactly 1 argument".to_string(),
         ));
     }
 
-    Ok(field.str() == request.args[0])
+    Ok(field == args[0])
 }
 
 pub fn string_contains_any(request: &Request, field: Value) -> Result<bool, CustomError> {
-    if request.args.is_empty() {
+    let (field, args) = case_folded(request, &field);
+
+    if args.is_empty() {
         return Err(CustomError::Error(
             "string_contains_any requires at least 1 argument".to_string(),
         ));
     }
 
-    for arg in &request.args {
-        if field.str().contains(arg) {
+    for arg in &args {
+        if field.contains(arg.as_str()) {
             return Ok(true);
         }
     }
@@ -37,14 +75,16 @@ pub fn string_contains_any(request: &Request, field: Value) -> Result<bool, Cust
 }
 
 pub fn string_contains_all(request: &Request, field: Value) -> Result<bool, CustomError> {
-    if request.args.is_empty() {
+    let (field, args) = case_folded(request, &field);
+
+    if args.is_empty() {
         return Err(CustomError::Error(
             "string_contains_any requires at least 1 argument".to_string(),
         ));
     }
 
-    for arg in &request.args {
-        if !field.str().contains(arg) {
+    for arg in &args {
+        if !field.contains(arg.as_str()) {
             return Ok(false);
         }
     }
@@ -52,6 +92,49 @@ pub fn string_contains_all(request: &Request, field: Value) -> Result<bool, Cust
     Ok(true)
 }
 
+/// The args, as given, that the string equal/contains matchers found in `field`,
+/// ie. which denied terms appeared for `string_contains_any`. Empty for other
+/// match types.
+pub fn matched_args(request: &Request, field: &Value) -> Vec<String> {
+    let compare: fn(&str, &str) -> bool = match request.match_type {
+        DetectiveType::DETECTIVE_TYPE_STRING_EQUAL => |field, arg| field == arg,
+        DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY
+        | DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL => |field, arg| field.contains(arg),
+        _ => return Vec::new(),
+    };
+
+    let (folded, args) = case_folded(request, field);
+
+    request
+        .args
+        .iter()
+        .filter(|a| a.as_str() != IGNORE_CASE)
+        .zip(args)
+        .filter(|(_, arg)| compare(&folded, arg))
+        .map(|(original, _)| original.clone())
+        .collect()
+}
+
+pub fn string_starts_with(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.is_empty() {
+        return Err(CustomError::Error(
+            "string_starts_with requires at least 1 argument".to_string(),
+        ));
+    }
+
+    Ok(request.args.iter().any(|arg| field.str().starts_with(arg.as_str())))
+}
+
+pub fn string_ends_with(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.is_empty() {
+        return Err(CustomError::Error(
+            "string_ends_with requires at least 1 argument".to_string(),
+        ));
+    }
+
+    Ok(request.args.iter().any(|arg| field.str().ends_with(arg.as_str())))
+}
+
 pub fn ip_address(request: &Request, field: Value) -> Result<bool, CustomError> {
     match request.match_type {
         DetectiveType::DETECTIVE_TYPE_IPV4_ADDRESS => {
@@ -66,27 +149,51 @@ pub fn ip_address(request: &Request, field: Value) -> Result<bool, CustomError>
     }
 }
 
-pub fn mac_address(_request: &Request, f: Value) -> Result<bool, CustomError> {
-    let field = f.str();
-    // Check if the string has the correct length
-    if field.len() != 17 {
-        return Ok(false);
+/// Option arg for MAC_ADDRESS: also match 8 octet EUI-64 addresses.
+pub const EUI64: &str = "eui64";
+
+// Colon (00:1a:2b:3c:4d:5e), hyphen (00-1A-2B-3C-4D-5E) or Cisco dotted
+// (001a.2b3c.4d5e) notation, see `normalize_mac()`.
+// Optional arg "eui64": 8 octet addresses match too
+pub fn mac_address(request: &Request, f: Value) -> Result<bool, CustomError> {
+    let allow_eui64 = request.args.iter().any(|a| a == EUI64);
+
+    Ok(normalize_mac(f.str(), allow_eui64).is_some())
+}
+
+/// Canonical lowercase colon form of a MAC address given in colon, hyphen or Cisco
+/// dotted notation, ie. "001A.2B3C.4D5E" becomes "00:1a:2b:3c:4d:5e". Separators
+/// can't be mixed. None when it isn't a 6 octet address, or 8 octet (EUI-64) one
+/// with `allow_eui64`.
+pub fn normalize_mac(value: &str, allow_eui64: bool) -> Option<String> {
+    let (separator, group_len) = if value.contains('.') {
+        ('.', 4)
+    } else if value.contains('-') {
+        ('-', 2)
+    } else {
+        (':', 2)
+    };
+
+    let groups: Vec<&str> = value.split(separator).collect();
+
+    if !groups
+        .iter()
+        .all(|g| g.len() == group_len && g.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return None;
     }
 
-    let s = str::replace(field, "-", ":");
+    let hex = groups.concat().to_ascii_lowercase();
 
-    // Iterate through each character and check if it's a valid MAC address character
-    for (i, c) in s.chars().enumerate() {
-        if i % 3 == 2 {
-            if c != ':' {
-                return Ok(false);
-            }
-        } else if !c.is_ascii_hexdigit() {
-            return Ok(false);
-        }
+    match hex.len() {
+        12 => {}
+        16 if allow_eui64 => {}
+        _ => return None,
     }
 
-    Ok(true)
+    let octets: Vec<&str> = (0..hex.len()).step_by(2).map(|i| &hex[i..i + 2]).collect();
+
+    Some(octets.join(":"))
 }
 
 pub fn uuid(_request: &Request, field: Value) -> Result<bool, CustomError> {
@@ -138,18 +245,46 @@ pub fn boolean_false(_request: &Request, field: Value) -> Result<bool, CustomErr
     Ok(!field.bool())
 }
 
+/// Option arg for IS_EMPTY: a path missing from the payload counts as empty
+/// instead of erroring.
+pub const MISSING_IS_EMPTY: &str = "missing_is_empty";
+
 // This is an all inclusive check - it'll return true if field is an empty string,
-// empty array or is null.
-pub fn is_empty(_request: &Request, field: Value) -> Result<bool, CustomError> {
-    match field.kind() {
+// empty array or is null. A missing path errors unless MISSING_IS_EMPTY is set,
+// in which case it is empty too.
+//
+// `negate` is applied to that result by `Detective::matches()`, so a negated
+// IS_EMPTY ("is not empty") never matches a missing path: with the flag it is
+// empty, without it the request errors.
+pub fn is_empty(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let result = match field.kind() {
+        _ if !field.exists() => {
+            if !request.args.iter().any(|a| a == MISSING_IS_EMPTY) {
+                return Err(CustomError::PathNotFound(request.path.clone()));
+            }
+
+            true
+        }
         // Null field
-        gjson::Kind::Null => Ok(true),
+        gjson::Kind::Null => true,
         // Maybe it's an array with 0 elements
-        gjson::Kind::Array => Ok(field.array().len() == 0),
+        gjson::Kind::Array => field.array().is_empty(),
         // Maybe an empty string?
-        gjson::Kind::String => Ok(field.to_string().len() == 0),
-        _ => Ok(false),
+        gjson::Kind::String => field.str().is_empty(),
+        _ => false,
+    };
+
+    Ok(result)
+}
+
+// Explicit JSON null only; "", 0, [], {} and false are not null. A missing path
+// errors, as it does for other matchers, so it can't be mistaken for a null.
+pub fn is_null(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if !field.exists() {
+        return Err(CustomError::PathNotFound(request.path.clone()));
     }
+
+    Ok(field.kind() == gjson::Kind::Null)
 }
 
 pub fn has_field(request: &Request, _field: Value) -> Result<bool, CustomError> {
@@ -181,17 +316,67 @@ pub fn is_type(request: &Request, field: Value) -> Result<bool, CustomError> {
     }
 }
 
+// Matches when any of the patterns in the args matches. Several patterns are
+// compiled into a single RegexSet so the field is scanned once.
 pub fn regex(request: &Request, field: Value) -> Result<bool, CustomError> {
-    if request.args.len() != 1 {
-        return Err(CustomError::Error(
-            "regex requires exactly 1 argument".to_string(),
-        ));
+    match request.args.len() {
+        0 => Err(CustomError::Error(
+            "regex requires at least 1 argument".to_string(),
+        )),
+        1 => Ok(cached_regex(&request.args[0])?.is_match(field.str())),
+        _ => Ok(cached_regex_set(&request.args)?.is_match(field.str())),
+    }
+}
+
+// Upper bound on cached patterns so rules built from dynamic input can't grow
+// the cache forever. The whole cache is dropped when it fills up.
+const REGEX_CACHE_SIZE: usize = 1024;
+
+lazy_static! {
+    // Matchers are plain fns without a handle to the `Detective`, so compiled
+    // patterns are shared process-wide. `Regex` clones are cheap.
+    static ref REGEX_CACHE: Mutex<HashMap<String, Regex>> = Mutex::new(HashMap::new());
+    static ref REGEX_SET_CACHE: Mutex<HashMap<Vec<String>, RegexSet>> = Mutex::new(HashMap::new());
+}
+
+/// Compile `pattern`, reusing the result of an earlier compile of the same pattern.
+/// The lock is only held for lookups and inserts, not while compiling, so one slow
+/// pattern doesn't stall every other thread's matchers.
+pub(crate) fn cached_regex(pattern: &str) -> Result<Regex, CustomError> {
+    // A panic while holding the lock can't leave the map inconsistent
+    if let Some(re) = REGEX_CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(pattern) {
+        return Ok(re.clone());
     }
 
-    let re_pattern = request.args[0].as_str();
-    let re = Regex::new(re_pattern)?;
+    let re = Regex::new(pattern)?;
 
-    Ok(re.is_match(field.str()))
+    let mut cache = REGEX_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+
+    if cache.len() >= REGEX_CACHE_SIZE && !cache.contains_key(pattern) {
+        cache.clear();
+    }
+
+    // Another thread may have compiled the same pattern in the meantime; keep theirs
+    Ok(cache.entry(pattern.to_string()).or_insert(re).clone())
+}
+
+/// Compile `patterns` into a `RegexSet`, reusing an earlier compile of the same
+/// patterns in the same order. Shares `REGEX_CACHE_SIZE` with `cached_regex()` and
+/// likewise compiles outside the lock.
+pub(crate) fn cached_regex_set(patterns: &[String]) -> Result<RegexSet, CustomError> {
+    if let Some(set) = REGEX_SET_CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(patterns) {
+        return Ok(set.clone());
+    }
+
+    let set = RegexSet::new(patterns)?;
+
+    let mut cache = REGEX_SET_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+
+    if cache.len() >= REGEX_CACHE_SIZE && !cache.contains_key(patterns) {
+        cache.clear();
+    }
+
+    Ok(cache.entry(patterns.to_vec()).or_insert(set).clone())
 }
 
 pub fn url(_request: &Request, field: Value) -> Result<bool, CustomError> {
@@ -229,6 +414,40 @@ pub fn string_length(request: &Request, f: Value) -> Result<bool, CustomError> {
     }
 }
 
+// Threshold for the string_length_* comparisons, a non-negative integer in args[0]
+fn length_threshold(request: &Request) -> Result<usize, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "string length comparison requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    request.args[0].trim().parse::<usize>().map_err(|_| {
+        CustomError::InvalidArgument(format!(
+            "string length '{}' is not a non-negative integer",
+            request.args[0]
+        ))
+    })
+}
+
+/// Length equal to `args[0]`. Unlike `string_length()`, which counts bytes, the
+/// string_length_* comparisons count Unicode scalar values, so "héllo" is 5 long.
+/// Grapheme clusters made of several scalars (ie. emoji with modifiers) count
+/// once per scalar.
+pub fn string_length_equal(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(field.str().chars().count() == length_threshold(request)?)
+}
+
+/// Length, in Unicode scalar values, greater than `args[0]`.
+pub fn string_length_greater_than(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(field.str().chars().count() > length_threshold(request)?)
+}
+
+/// Length, in Unicode scalar values, less than `args[0]`.
+pub fn string_length_less_than(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(field.str().chars().count() < length_threshold(request)?)
+}
+
 pub fn hostname(_request: &Request, field: Value) -> Result<bool, CustomError> {
     fn is_valid_char(byte: u8) -> bool {
         byte.is_ascii_lowercase()
@@ -252,3 +471,1473 @@ pub fn hostname(_request: &Request, field: Value) -> Result<bool, CustomError> {
 pub fn semver(_request: &Request, field: Value) -> Result<bool, CustomError> {
     semver::Version::parse(field.str()).map_or(Ok(false), |_| Ok(true))
 }
+
+// Args: relational operator, version to compare against (ie. ">=", "1.5.0").
+// Prereleases sort before their release and build metadata is ignored, as per
+// the semver spec, so "1.5.0-rc.1" < "1.5.0" == "1.5.0+build.7".
+pub fn semver_compare(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "semver_compare requires exactly 2 arguments".to_string(),
+        ));
+    }
+
+    let mut version = semver::Version::parse(request.args[1].trim()).map_err(|e| {
+        CustomError::InvalidArgument(format!("'{}' is not a semver: {}", request.args[1], e))
+    })?;
+
+    let mut field_version = semver::Version::parse(field.str().trim()).map_err(|e| {
+        CustomError::MatchError(format!(
+            "field value '{}' is not a semver: {}",
+            field.str(),
+            e
+        ))
+    })?;
+
+    version.build = semver::BuildMetadata::EMPTY;
+    field_version.build = semver::BuildMetadata::EMPTY;
+
+    compare(request.args[0].as_str(), field_version, version)
+}
+
+// Args: relational operator, number of distinct characters to compare against.
+// Low counts indicate repeated junk ("aaaaaaaa"), high counts binary-ish blobs.
+pub fn distinct_chars(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "distinct_chars requires exactly 2 arguments".to_string(),
+        ));
+    }
+
+    let threshold = parse_number(&request.args[1])?;
+    let distinct: std::collections::HashSet<char> = field.str().chars().collect();
+
+    compare(request.args[0].as_str(), distinct.len() as f64, threshold)
+}
+
+// Emoji/pictograph blocks plus the joiners and modifiers emoji sequences are built from
+fn is_emoji_or_symbol(c: char) -> bool {
+    matches!(c as u32,
+        0x2190..=0x21FF // arrows
+        | 0x2300..=0x23FF // misc technical, ie. ⌚ ⏰
+        | 0x2600..=0x27BF // misc symbols, dingbats
+        | 0x2B00..=0x2BFF // misc symbols and arrows, ie. ⭐
+        | 0x1F000..=0x1FAFF // mahjong through symbols and pictographs extended-a
+        | 0x200D // zero width joiner
+        | 0x20E3 // combining enclosing keycap
+        | 0xFE0F // emoji variation selector
+        | 0xE0020..=0xE007F // tags used by flag sequences
+    )
+}
+
+// Args: relational operator, ratio (0-1) of emoji/symbol characters to compare against.
+// Whitespace is not counted towards the total; an empty string has a ratio of 0.
+pub fn emoji_ratio(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "emoji_ratio requires exactly 2 arguments".to_string(),
+        ));
+    }
+
+    let threshold = parse_number(&request.args[1])?;
+
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(CustomError::Error(format!(
+            "emoji_ratio threshold must be between 0 and 1, got {}",
+            threshold
+        )));
+    }
+
+    let (total, symbols) = field
+        .str()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold((0, 0), |(total, symbols), c| {
+            (total + 1, symbols + is_emoji_or_symbol(c) as usize)
+        });
+
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        symbols as f64 / total as f64
+    };
+
+    compare(request.args[0].as_str(), ratio, threshold)
+}
+
+/// Return the first value that appears more than once in the array `field`, as raw
+/// JSON (so `1` and `"1"` are distinct). With `sub_path`, each element's value at
+/// that path is compared instead of the whole element.
+pub fn find_duplicate(field: &Value, sub_path: Option<&str>) -> Result<Option<String>, CustomError> {
+    if field.kind() != gjson::Kind::Array {
+        return Err(CustomError::MatchError(
+            "array_unique requires an array field".to_string(),
+        ));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+
+    for (i, element) in field.array().iter().enumerate() {
+        let value = match sub_path {
+            Some(sub_path) => element.get(sub_path),
+            None => gjson::parse(element.json()),
+        };
+
+        if !value.exists() {
+            return Err(CustomError::MatchError(format!(
+                "array element {} has no value at '{}'",
+                i,
+                sub_path.unwrap_or_default()
+            )));
+        }
+
+        if !seen.insert(value.json().to_string()) {
+            return Ok(Some(value.json().to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+// Args: optional path within each element to compare, ie. "id" for an array of objects
+pub fn array_unique(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() > 1 {
+        return Err(CustomError::Error(
+            "array_unique accepts at most 1 argument".to_string(),
+        ));
+    }
+
+    let duplicate = find_duplicate(&field, request.args.first().map(String::as_str))?;
+
+    Ok(duplicate.is_none())
+}
+
+/// Index of the first element of the array `field` that is out of order for
+/// `direction` ("asc" or "desc"), or None when it's sorted. Equal neighbours are
+/// in order. Numbers compare numerically and strings lexicographically by code
+/// point; an array mixing them, or holding other types, is an error.
+pub fn find_unsorted(field: &Value, direction: &str) -> Result<Option<usize>, CustomError> {
+    if field.kind() != gjson::Kind::Array {
+        return Err(CustomError::MatchError(
+            "array_sorted requires an array field".to_string(),
+        ));
+    }
+
+    let descending = match direction {
+        "asc" | "ascending" => false,
+        "desc" | "descending" => true,
+        _ => {
+            return Err(CustomError::InvalidArgument(format!(
+                "unknown sort direction '{}': must be asc or desc",
+                direction
+            )))
+        }
+    };
+
+    let elements = field.array();
+
+    let kind = match elements.first() {
+        Some(first) => first.kind(),
+        None => return Ok(None),
+    };
+
+    if kind != gjson::Kind::Number && kind != gjson::Kind::String {
+        return Err(CustomError::MatchError(
+            "array_sorted requires an array of numbers or strings".to_string(),
+        ));
+    }
+
+    for (i, pair) in elements.windows(2).enumerate() {
+        if pair[1].kind() != kind {
+            return Err(CustomError::MatchError(format!(
+                "array element {} is not the same type as the first element",
+                i + 1
+            )));
+        }
+
+        let ordering = if kind == gjson::Kind::Number {
+            pair[0].f64().partial_cmp(&pair[1].f64())
+        } else {
+            Some(pair[0].str().cmp(pair[1].str()))
+        };
+
+        let in_order = match ordering {
+            Some(std::cmp::Ordering::Less) => !descending,
+            Some(std::cmp::Ordering::Greater) => descending,
+            Some(std::cmp::Ordering::Equal) => true,
+            None => false,
+        };
+
+        if !in_order {
+            return Ok(Some(i + 1));
+        }
+    }
+
+    Ok(None)
+}
+
+// Args: sort direction, "asc" or "desc"
+pub fn array_sorted(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "array_sorted requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    Ok(find_unsorted(&field, &request.args[0])?.is_none())
+}
+
+// Syslog severity (0 = emergency .. 7 = debug) for a level name or number
+fn syslog_severity(level: &str) -> Option<u8> {
+    let level = level.trim();
+
+    if let Ok(n) = level.parse::<u8>() {
+        return (n <= 7).then_some(n);
+    }
+
+    match level.to_ascii_lowercase().as_str() {
+        "emerg" | "emergency" | "panic" => Some(0),
+        "alert" => Some(1),
+        "crit" | "critical" | "fatal" => Some(2),
+        "err" | "error" => Some(3),
+        "warn" | "warning" => Some(4),
+        "notice" => Some(5),
+        "info" | "informational" => Some(6),
+        "debug" => Some(7),
+        _ => None,
+    }
+}
+
+// Recognized log level names (case-insensitive) or syslog levels 0-7.
+// Args: optional relational operator and level to compare severity against, ie.
+// ["gte", "warn"] matches warn, error and fatal. Higher severity compares greater.
+pub fn log_level(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let severity = match syslog_severity(field.str()) {
+        Some(severity) => severity,
+        None => return Ok(false),
+    };
+
+    match request.args.len() {
+        0 => Ok(true),
+        2 => {
+            let threshold = syslog_severity(&request.args[1]).ok_or_else(|| {
+                CustomError::Error(format!("unknown log level: {}", request.args[1]))
+            })?;
+
+            // Syslog counts down as severity goes up
+            compare(request.args[0].as_str(), 7 - severity, 7 - threshold)
+        }
+        _ => Err(CustomError::Error(
+            "log_level accepts either 0 or 2 arguments".to_string(),
+        )),
+    }
+}
+
+/// Parse a Go style (`1h30m`, `500ms`, `1.5s`, also `d` for days) or ISO8601
+/// (`PT1H30M`, `P2D`, `P1W`) duration. Years and months have no fixed length
+/// and are rejected.
+pub fn parse_duration(input: &str) -> Result<std::time::Duration, CustomError> {
+    let input = input.trim();
+    let invalid = || CustomError::Error(format!("invalid duration: '{}'", input));
+
+    let (units, rest, iso): (&[(&str, f64)], &str, bool) = match input.strip_prefix(['P', 'p']) {
+        Some(rest) => (
+            &[("W", 604800.0), ("D", 86400.0), ("T", 0.0), ("H", 3600.0), ("M", 60.0), ("S", 1.0)],
+            rest,
+            true,
+        ),
+        None => (
+            &[
+                ("ns", 1e-9),
+                ("us", 1e-6),
+                ("µs", 1e-6),
+                ("ms", 1e-3),
+                ("s", 1.0),
+                ("m", 60.0),
+                ("h", 3600.0),
+                ("d", 86400.0),
+            ],
+            input,
+            false,
+        ),
+    };
+
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut rest = rest;
+    let mut seconds = 0.0;
+    let mut in_time = false;
+
+    while !rest.is_empty() {
+        if iso && rest.starts_with(['T', 't']) && !in_time {
+            in_time = true;
+            rest = &rest[1..];
+            continue;
+        }
+
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        let number: f64 = rest[..number_len].parse().map_err(|_| invalid())?;
+        rest = &rest[number_len..];
+
+        // Longest unit first so "ms" isn't read as "m"
+        let (unit, multiplier) = units
+            .iter()
+            .filter(|(unit, _)| *unit != "T")
+            .filter(|(unit, _)| {
+                if iso {
+                    rest.to_ascii_uppercase().starts_with(unit)
+                } else {
+                    rest.starts_with(unit)
+                }
+            })
+            .max_by_key(|(unit, _)| unit.len())
+            .ok_or_else(invalid)?;
+
+        // ISO8601 "M" is months before the "T" and minutes after it
+        if iso && (in_time != matches!(*unit, "H" | "M" | "S")) {
+            return Err(invalid());
+        }
+
+        seconds += number * multiplier;
+        rest = &rest[unit.len()..];
+    }
+
+    std::time::Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+// Args: optional relational operator and duration to compare against, ie. ["lt", "1s"].
+// Without args any valid duration matches; when comparing, an invalid field errors.
+pub fn duration(request: &Request, field: Value) -> Result<bool, CustomError> {
+    match request.args.len() {
+        0 => Ok(parse_duration(field.str()).is_ok()),
+        2 => {
+            let threshold = parse_duration(&request.args[1])?;
+
+            compare(request.args[0].as_str(), parse_duration(field.str())?, threshold)
+        }
+        _ => Err(CustomError::Error(
+            "duration accepts either 0 or 2 arguments".to_string(),
+        )),
+    }
+}
+
+// Compile a printf style template into an anchored regex with one capture group
+// per placeholder. Supported placeholders:
+//
+//   %d     one or more digits, optionally negative
+//   %0Nd   exactly N digits (zero padded), ie. %04d
+//   %x     one or more hex digits
+//   %f     a decimal number, ie. -1.5
+//   %s     one or more characters
+//   %Ns    exactly N characters
+//   %%     a literal '%'
+fn compile_template(template: &str) -> Result<Regex, CustomError> {
+    let invalid = |msg: &str| {
+        CustomError::InvalidArgument(format!("invalid template '{}': {}", template, msg))
+    };
+
+    let mut pattern = String::from("^");
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            pattern.push_str(&regex::escape(&c.to_string()));
+            continue;
+        }
+
+        let mut width = String::new();
+
+        while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            width.push(*d);
+            chars.next();
+        }
+
+        let placeholder = match (chars.next(), width.as_str()) {
+            (Some('%'), "") => "%".to_string(),
+            (Some('d'), "") => r"(-?\d+)".to_string(),
+            (Some('d'), w) if w.len() > 1 && w.starts_with('0') => {
+                format!(r"(\d{{{}}})", &w[1..])
+            }
+            (Some('x'), "") => r"([0-9a-fA-F]+)".to_string(),
+            (Some('f'), "") => r"(-?\d+(?:\.\d+)?)".to_string(),
+            (Some('s'), "") => r"(.+?)".to_string(),
+            (Some('s'), w) => format!(r"(.{{{}}})", w),
+            (Some(p), w) => {
+                return Err(invalid(&format!("unsupported placeholder '%{}{}'", w, p)))
+            }
+            (None, _) => return Err(invalid("dangling '%'")),
+        };
+
+        pattern.push_str(&placeholder);
+    }
+
+    pattern.push('$');
+
+    cached_regex(&pattern)
+}
+
+/// Values of each placeholder in `template` (see `compile_template()`) if `value`
+/// conforms to it, ie. "ORD-%04d-%s" on "ORD-0042-blue" gives ["0042", "blue"].
+pub fn template_values(template: &str, value: &str) -> Result<Option<Vec<String>>, CustomError> {
+    let re = compile_template(template)?;
+
+    Ok(re.captures(value).map(|caps| {
+        caps.iter()
+            .skip(1)
+            .map(|m| m.map_or(String::new(), |m| m.as_str().to_string()))
+            .collect()
+    }))
+}
+
+// True when the field conforms to the printf style template given as the only arg,
+// ie. "ORD-%04d-%s". Friendlier than a regex for structured IDs.
+pub fn template(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "template requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    Ok(template_values(&request.args[0], field.str())?.is_some())
+}
+
+lazy_static! {
+    // (name, category, pattern). Keywords are matched on word boundaries so that
+    // ie. "selection" or "please select one" don't trip the SQL signatures.
+    static ref INJECTION_SIGNATURES: Vec<(&'static str, &'static str, Regex)> = vec![
+        ("union_select", "sql", Regex::new(r"(?i)\bunion\s+(all\s+)?select\b").unwrap()),
+        ("tautology", "sql", Regex::new(r#"(?i)['"]\s*or\s+['"]?(\w+)['"]?\s*=\s*['"]?(\w+)"#).unwrap()),
+        ("comment_terminator", "sql", Regex::new(r"'\s*(--|#|/\*)").unwrap()),
+        ("stacked_query", "sql", Regex::new(r"(?i);\s*(drop|delete|truncate|alter|insert|update|exec)\s").unwrap()),
+        ("time_delay", "sql", Regex::new(r"(?i)\b(sleep|pg_sleep|benchmark)\s*\(|\bwaitfor\s+delay\b").unwrap()),
+        ("backtick_exec", "command", Regex::new(r"`[^`]*[;|&$<>()][^`]*`").unwrap()),
+        ("subshell", "command", Regex::new(r"\$\([^)]*\)").unwrap()),
+        ("chained_command", "command", Regex::new(r"(?:;|&&|\|\|?)\s*(rm|cat|curl|wget|nc|bash|sh|chmod|whoami|uname)\b").unwrap()),
+    ];
+}
+
+/// Names of the injection signatures found in `value`, limited to `selectors`
+/// (signature names or categories: "sql", "command") when any are given.
+pub fn find_injection_signatures(
+    value: &str,
+    selectors: &[String],
+) -> Result<Vec<&'static str>, CustomError> {
+    for selector in selectors {
+        if !INJECTION_SIGNATURES
+            .iter()
+            .any(|(name, category, _)| selector == name || selector == category)
+        {
+            return Err(CustomError::InvalidArgument(format!(
+                "unknown injection signature or category '{}'",
+                selector
+            )));
+        }
+    }
+
+    Ok(INJECTION_SIGNATURES
+        .iter()
+        .filter(|(name, category, _)| {
+            selectors.is_empty() || selectors.iter().any(|s| s == name || s == category)
+        })
+        .filter(|(_, _, re)| re.is_match(value))
+        .map(|(name, _, _)| *name)
+        .collect())
+}
+
+// Heuristic SQL/command injection detection for WAF style scanning. Optional
+// args narrow the signature set, ie. "sql" or "union_select" (see
+// INJECTION_SIGNATURES). Expect false positives and negatives.
+pub fn injection(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(!find_injection_signatures(field.str(), &request.args)?.is_empty())
+}
+
+// Split the field on a delimiter and run a nested matcher on one segment.
+// Args: delimiter, index (negative counts from the end), matcher name as used
+// in expressions (ie. "numeric_greater_than" or "glob"), then the nested
+// matcher's args. A field with too few segments doesn't match.
+pub fn segment(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() < 3 {
+        return Err(CustomError::Error(
+            "segment requires at least 3 arguments".to_string(),
+        ));
+    }
+
+    let delimiter = request.args[0].as_str();
+
+    if delimiter.is_empty() {
+        return Err(CustomError::Error(
+            "segment delimiter cannot be empty".to_string(),
+        ));
+    }
+
+    let index: i64 = request.args[1].parse().map_err(|_| {
+        CustomError::Error(format!("invalid segment index: {}", request.args[1]))
+    })?;
+
+    let matcher = crate::expr::resolve_matcher(&request.args[2])?;
+
+    if matcher == Matcher::Type(DetectiveType::DETECTIVE_TYPE_HAS_FIELD) {
+        return Err(CustomError::Error(
+            "has_field cannot be used on a segment".to_string(),
+        ));
+    }
+
+    let segments: Vec<&str> = field.str().split(delimiter).collect();
+
+    let index = if index < 0 {
+        segments.len() as i64 + index
+    } else {
+        index
+    };
+
+    let segment = match usize::try_from(index).ok().and_then(|i| segments.get(i)) {
+        Some(segment) => *segment,
+        None => return Ok(false),
+    };
+
+    let nested = Request {
+        match_type: match matcher {
+            Matcher::Type(match_type) => match_type,
+            Matcher::Named(_) => DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        },
+        data: request.data,
+        path: request.path.clone(),
+        args: request.args[3..].to_vec(),
+        negate: false,
+    };
+
+    let json = serde_json::Value::String(segment.to_string()).to_string();
+    let f = match matcher {
+        Matcher::Type(_) => Detective::get_matcher_func(&nested)?,
+        Matcher::Named(name) => named_matcher(name)
+            .map(|(_, f)| f)
+            .ok_or_else(|| CustomError::MatchError(format!("unknown matcher: {}", name)))?,
+    };
+
+    f(&nested, gjson::parse(&json))
+}
+
+// True when characters from more than one script are present, ie. a Cyrillic 'а'
+// in an otherwise Latin "pаypal.com". Digits, punctuation and combining marks
+// (the Common and Inherited scripts) don't count towards any script.
+pub fn mixed_script(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    let mut first: Option<Script> = None;
+
+    for c in field.str().chars() {
+        let script = c.script();
+
+        if matches!(script, Script::Common | Script::Inherited | Script::Unknown) {
+            continue;
+        }
+
+        match first {
+            None => first = Some(script),
+            Some(first) if first != script => return Ok(true),
+            _ => {}
+        }
+    }
+
+    Ok(false)
+}
+
+// True when the field compiles as a regex pattern. Optional arg "error_on_invalid":
+// return the compile error instead of false.
+pub fn is_regex(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let error_on_invalid = match request.args.first().map(String::as_str) {
+        None => false,
+        Some("error_on_invalid") if request.args.len() == 1 => true,
+        Some(_) => {
+            return Err(CustomError::Error(
+                "is_regex accepts only the 'error_on_invalid' argument".to_string(),
+            ))
+        }
+    };
+
+    match Regex::new(field.str()) {
+        Ok(_) => Ok(true),
+        Err(err) if error_on_invalid => Err(err.into()),
+        Err(_) => Ok(false),
+    }
+}
+
+// True when the field is already in the normalization form given as the only
+// arg (NFC, NFD, NFKC or NFKD), ie. normalizing it would be a no-op.
+pub fn is_normalized(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "is_normalized requires exactly one argument: the normalization form".to_string(),
+        ));
+    }
+
+    let value = field.str();
+
+    match request.args[0].to_ascii_uppercase().as_str() {
+        "NFC" => Ok(is_nfc(value)),
+        "NFD" => Ok(is_nfd(value)),
+        "NFKC" => Ok(is_nfkc(value)),
+        "NFKD" => Ok(is_nfkd(value)),
+        form => Err(CustomError::InvalidArgument(format!(
+            "unknown normalization form '{}', expected NFC, NFD, NFKC or NFKD",
+            form
+        ))),
+    }
+}
+
+// #RGB, #RGBA, #RRGGBB or #RRGGBBAA
+pub fn hex_color(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    let hex = match field.str().strip_prefix('#') {
+        Some(hex) => hex,
+        None => return Ok(false),
+    };
+
+    Ok(matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// Args: the other timestamp and a tolerance duration (see `parse_duration()`), ie.
+// ["event.received_at", "5s"]. The other timestamp is looked up as a path in the
+// payload first and otherwise parsed as a literal timestamp. Matches when the
+// absolute difference is within the tolerance.
+pub fn timestamp_close(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "timestamp_close requires exactly 2 arguments".to_string(),
+        ));
+    }
+
+    let tolerance = parse_duration(&request.args[1])?;
+    let ts = parse_timestamp(&field)?;
+
+    let data_as_str = str::from_utf8(request.data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    let other = match gjson::get(data_as_str, &request.args[0]) {
+        other if other.exists() => parse_timestamp(&other)?,
+        _ => {
+            let literal = serde_json::Value::String(request.args[0].clone()).to_string();
+            parse_timestamp(&gjson::parse(&literal))?
+        }
+    };
+
+    let difference = (ts - other).abs().to_std().map_err(|e| {
+        CustomError::Error(format!("unable to compare timestamps: {}", e))
+    })?;
+
+    Ok(difference <= tolerance)
+}
+
+// An RFC3339 timestamp arg, normalized to UTC
+fn timestamp_arg(arg: &str) -> Result<chrono::DateTime<chrono::Utc>, CustomError> {
+    chrono::DateTime::parse_from_rfc3339(arg.trim())
+        .map(|ts| ts.with_timezone(&chrono::Utc))
+        .map_err(|e| {
+            CustomError::InvalidArgument(format!("'{}' is not an RFC3339 timestamp: {}", arg, e))
+        })
+}
+
+// Arg: an RFC3339 timestamp, ie. "2024-01-01T00:00:00Z". The field is parsed with
+// parse_timestamp(). Both sides are compared in UTC, so "2024-01-01T00:00:00Z",
+// "2024-01-01T00:00:00+00:00" and "2023-12-31T19:00:00-05:00" are the same instant.
+pub fn timestamp_after(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "timestamp_after requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    Ok(parse_timestamp(&field)? > timestamp_arg(&request.args[0])?)
+}
+
+// Arg: an RFC3339 timestamp, see timestamp_after()
+pub fn timestamp_before(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "timestamp_before requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    Ok(parse_timestamp(&field)? < timestamp_arg(&request.args[0])?)
+}
+
+// Args: start and end RFC3339 timestamps, see timestamp_after(). Inclusive on
+// both ends; errors when start is after end.
+pub fn timestamp_between(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "timestamp_between requires exactly 2 arguments".to_string(),
+        ));
+    }
+
+    let start = timestamp_arg(&request.args[0])?;
+    let end = timestamp_arg(&request.args[1])?;
+
+    if start > end {
+        return Err(CustomError::InvalidArgument(format!(
+            "timestamp_between start '{}' is after end '{}'",
+            request.args[0], request.args[1]
+        )));
+    }
+
+    let ts = parse_timestamp(&field)?;
+
+    Ok(start <= ts && ts <= end)
+}
+
+// Arg: the previously seen timestamp, in any form parse_timestamp() accepts.
+// Matches when the field is at or after it, ie. events arrive in order. Equal
+// timestamps count as in order.
+pub fn timestamp_monotonic(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "timestamp_monotonic requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    let literal = serde_json::Value::String(request.args[0].clone()).to_string();
+    let prior = parse_timestamp(&gjson::parse(&literal)).map_err(|e| {
+        CustomError::InvalidArgument(format!("prior timestamp '{}': {}", request.args[0], e))
+    })?;
+
+    Ok(parse_timestamp(&field)? >= prior)
+}
+
+// Standard alphabet base64, padded or not. Optional arg "wrapped": ignore line
+// breaks and other whitespace, as in PEM or MIME bodies wrapped at 64/76 columns.
+pub fn is_base64(request: &Request, field: Value) -> Result<bool, CustomError> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+
+    let wrapped = request.args.iter().any(|a| a == "wrapped");
+    let value = field.str().trim();
+
+    let value = if wrapped {
+        value.chars().filter(|c| !c.is_whitespace()).collect()
+    } else {
+        value.to_string()
+    };
+
+    if value.is_empty() {
+        return Ok(false);
+    }
+
+    Ok(STANDARD.decode(&value).is_ok() || STANDARD_NO_PAD.decode(&value).is_ok())
+}
+
+// True when the field's bytes end partway through a multibyte UTF-8 sequence, ie.
+// a string that was cut mid-codepoint. JSON strings are always valid UTF-8, so the
+// bytes have to come from a binary-safe encoding: a base64 string or an array of
+// byte values. Bytes that are invalid elsewhere but end cleanly don't match.
+pub fn truncated_utf8(request: &Request, field: Value) -> Result<bool, CustomError> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+
+    let invalid = || {
+        CustomError::InvalidField(format!(
+            "field '{}' is not base64 or an array of bytes",
+            request.path
+        ))
+    };
+
+    let bytes: Vec<u8> = match field.kind() {
+        gjson::Kind::String => STANDARD
+            .decode(field.str())
+            .or_else(|_| STANDARD_NO_PAD.decode(field.str()))
+            .map_err(|_| invalid())?,
+        gjson::Kind::Array => field
+            .array()
+            .iter()
+            .map(|b| match b.kind() {
+                gjson::Kind::Number if b.u64() <= 255 && b.f64().fract() == 0.0 => Ok(b.u64() as u8),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(invalid()),
+    };
+
+    // The last (up to 3) bytes are enough to find an incomplete sequence, and avoid
+    // tripping over unrelated invalid bytes earlier in the field
+    let tail = &bytes[bytes.len().saturating_sub(3)..];
+    let start = tail.iter().rposition(|b| b & 0b1100_0000 != 0b1000_0000);
+
+    Ok(match start {
+        Some(start) => matches!(
+            str::from_utf8(&tail[start..]),
+            Err(e) if e.valid_up_to() == 0 && e.error_len().is_none()
+        ),
+        None => false,
+    })
+}
+
+fn is_ipv4(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    IpAddr::from_str(field.str()).map_or(Ok(false), |i| Ok(i.is_ipv4()))
+}
+
+fn is_ipv6(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    IpAddr::from_str(field.str()).map_or(Ok(false), |i| Ok(i.is_ipv6()))
+}
+
+/// An IP network in CIDR notation, ie. `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Debug, PartialEq)]
+pub struct Cidr {
+    pub network: IpAddr,
+    pub prefix_len: u8,
+}
+
+impl Cidr {
+    pub fn parse(input: &str) -> Result<Cidr, CustomError> {
+        let invalid =
+            |msg: &str| CustomError::InvalidArgument(format!("invalid CIDR '{}': {}", input, msg));
+
+        let (addr, prefix_len) = input
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| invalid("missing '/'"))?;
+        let network = IpAddr::from_str(addr).map_err(|_| invalid("bad address"))?;
+        let prefix_len: u8 = prefix_len.parse().map_err(|_| invalid("bad prefix length"))?;
+
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+
+        if prefix_len > max_len {
+            return Err(invalid(&format!("prefix length must be at most {}", max_len)));
+        }
+
+        Ok(Cidr { network, prefix_len })
+    }
+
+    /// An address of the other IP version is never contained.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        let (network, addr, bits) = match (self.network, addr) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                (u32::from(network) as u128, u32::from(*addr) as u128, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => (u128::from(network), u128::from(*addr), 128),
+            _ => return false,
+        };
+
+        if self.prefix_len == 0 {
+            return true;
+        }
+
+        let shift = bits - self.prefix_len as u32;
+
+        network >> shift == addr >> shift
+    }
+}
+
+// True when the field is an IP address inside any of the CIDR networks given as
+// args, ie. "10.0.0.0/8". Fields that aren't an IP address don't match.
+pub fn ip_cidr(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.is_empty() {
+        return Err(CustomError::Error(
+            "ip_cidr requires at least 1 argument".to_string(),
+        ));
+    }
+
+    // Parse every network up front so a malformed arg always errors
+    let networks = request
+        .args
+        .iter()
+        .map(|arg| Cidr::parse(arg))
+        .collect::<Result<Vec<Cidr>, CustomError>>()?;
+
+    let addr = match IpAddr::from_str(field.str().trim()) {
+        Ok(addr) => addr,
+        Err(_) => return Ok(false),
+    };
+
+    Ok(networks.iter().any(|network| network.contains(&addr)))
+}
+
+/// Format validators addressable by name in `any_format()` args
+pub fn format_validator(name: &str) -> Option<MatcherFunc> {
+    let f: MatcherFunc = match name {
+        "uuid" => uuid,
+        "email" => pii::email,
+        "phone" => pii::phone,
+        "credit_card" => pii::credit_card,
+        "ipv4" => is_ipv4,
+        "ipv6" => is_ipv6,
+        "mac_address" => mac_address,
+        "url" => url,
+        "hostname" => hostname,
+        "semver" => semver,
+        "timestamp_rfc3339" => timestamp_rfc3339,
+        "timestamp_unix" => timestamp_unix,
+        "timestamp_unix_nano" => timestamp_unix_nano,
+        _ => return None,
+    };
+
+    Some(f)
+}
+
+// Args are format names (see format_validator()); true if any of them validates.
+// A validator that errors (ie. not implemented) is treated as not matching.
+pub fn any_format(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.is_empty() {
+        return Err(CustomError::Error(
+            "any_format requires at least 1 argument".to_string(),
+        ));
+    }
+
+    let validators = request
+        .args
+        .iter()
+        .map(|name| {
+            format_validator(name)
+                .ok_or_else(|| CustomError::MatchError(format!("unknown format: {}", name)))
+        })
+        .collect::<Result<Vec<MatcherFunc>, CustomError>>()?;
+
+    // Validators must not see our format names as their own args (ie. phone would
+    // read "uuid" as a country hint), so call each one with its defaults.
+    let bare = Request {
+        args: vec![],
+        ..request.clone()
+    };
+
+    Ok(validators
+        .iter()
+        .any(|f| f(&bare, gjson::parse(field.json())).unwrap_or(false)))
+}
+
+// RFC 4648 base32 (A-Z, 2-7, '=' padding). Base64 strings are rejected by virtue of
+// lowercase letters and digits outside 2-7. With a "decode" arg the unused trailing
+// bits must also be zero, ie. the value round-trips exactly as a decoder would produce it.
+pub fn is_base32(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let strict_decode = request.args.iter().any(|a| a == "decode");
+    let value = field.str();
+
+    let data = value.trim_end_matches('=');
+    let padding = value.len() - data.len();
+
+    if data.is_empty() {
+        return Ok(false);
+    }
+
+    // Padding, when present, must fill out the final 8 char block. A 2, 4, 5 or 7
+    // char final block is padded with 6, 4, 3 or 1 '='; a whole block of '=' is invalid.
+    if !matches!(padding, 0 | 1 | 3 | 4 | 6) || (padding > 0 && !value.len().is_multiple_of(8)) {
+        return Ok(false);
+    }
+
+    // Number of chars in the final block; each valid count maps to a whole number of bytes
+    let trailing_bits = match data.len() % 8 {
+        0 => 0,
+        2 => 2,
+        4 => 4,
+        5 => 1,
+        7 => 3,
+        _ => return Ok(false),
+    };
+
+    let mut last = 0;
+
+    for c in data.bytes() {
+        last = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return Ok(false),
+        };
+    }
+
+    if strict_decode && last & ((1 << trailing_bits) - 1) != 0 {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// True when `value` is a non-empty object whose keys are exactly "0".."n-1",
+/// ie. an array that was serialized as an object. Key order doesn't matter.
+pub fn is_array_like_object(value: &Value) -> bool {
+    if value.kind() != gjson::Kind::Object {
+        return false;
+    }
+
+    let mut indexes: Vec<usize> = Vec::new();
+    let mut valid = true;
+
+    value.each(|key, _| {
+        match key.str().parse::<usize>() {
+            // Reject "01", "+1" etc. which parse but aren't canonical indexes
+            Ok(i) if i.to_string() == key.str() => indexes.push(i),
+            _ => valid = false,
+        }
+        valid
+    });
+
+    indexes.sort_unstable();
+
+    valid && !indexes.is_empty() && indexes.iter().enumerate().all(|(i, &k)| i == k)
+}
+
+pub fn sequential_keys(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(is_array_like_object(&field))
+}
+
+// Digits of an identifier with hyphens/spaces removed. A trailing 'X' (check digit
+// of 10) is mapped to 10; anything else non-numeric yields None.
+fn identifier_digits(value: &str) -> Option<Vec<u32>> {
+    let cleaned: Vec<char> = value.trim().chars().filter(|c| *c != '-' && *c != ' ').collect();
+
+    cleaned
+        .iter()
+        .enumerate()
+        .map(|(i, c)| match c {
+            'X' | 'x' if i == cleaned.len() - 1 => Some(10),
+            c => c.to_digit(10),
+        })
+        .collect()
+}
+
+// EAN-13 style checksum: weights alternate 1, 3 from the left
+fn ean_checksum_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+// ISBN-10 (check digit may be 'X') or ISBN-13 (978/979 prefix, EAN check digit)
+pub fn isbn(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    let digits = match identifier_digits(field.str()) {
+        Some(digits) => digits,
+        None => return Ok(false),
+    };
+
+    match digits.len() {
+        10 => {
+            if digits[..9].contains(&10) {
+                return Ok(false);
+            }
+
+            let sum: u32 = digits
+                .iter()
+                .enumerate()
+                .map(|(i, d)| (10 - i as u32) * d)
+                .sum();
+
+            Ok(sum.is_multiple_of(11))
+        }
+        13 => {
+            let bookland = digits.starts_with(&[9, 7, 8]) || digits.starts_with(&[9, 7, 9]);
+
+            if digits.contains(&10) || !bookland {
+                return Ok(false);
+            }
+
+            Ok(ean_checksum_valid(&digits))
+        }
+        _ => Ok(false),
+    }
+}
+
+// UPC-A (12 digits), EAN-13 or EAN-8 product barcodes. Args: optional symbology
+// ("upc_a", "ean_13", "ean_8"), otherwise detected from the number of digits.
+pub fn barcode(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() > 1 {
+        return Err(CustomError::Error(
+            "barcode accepts at most 1 argument".to_string(),
+        ));
+    }
+
+    let expected_len = match request.args.first().map(String::as_str) {
+        None => None,
+        Some("upc_a") => Some(12),
+        Some("ean_13") => Some(13),
+        Some("ean_8") => Some(8),
+        Some(symbology) => {
+            return Err(CustomError::Error(format!(
+                "unknown barcode symbology '{}': must be one of upc_a, ean_13, ean_8",
+                symbology
+            )))
+        }
+    };
+
+    let digits = match identifier_digits(field.str()) {
+        Some(digits) if !digits.contains(&10) => digits,
+        _ => return Ok(false),
+    };
+
+    match (digits.len(), expected_len) {
+        (len, Some(expected)) if len != expected => Ok(false),
+        // Weights alternate from the check digit, so pad even lengths to line up with EAN-13
+        (12 | 8, _) => Ok(ean_checksum_valid(&[&[0], &digits[..]].concat())),
+        (13, _) => Ok(ean_checksum_valid(&digits)),
+        _ => Ok(false),
+    }
+}
+
+// ISSN: 7 digits + check digit (may be 'X'), usually written as NNNN-NNNC
+pub fn issn(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    let digits = match identifier_digits(field.str()) {
+        Some(digits) if digits.len() == 8 && !digits[..7].contains(&10) => digits,
+        _ => return Ok(false),
+    };
+
+    let sum: u32 = digits[..7]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (8 - i as u32) * d)
+        .sum();
+
+    Ok((11 - sum % 11) % 11 == digits[7])
+}
+
+/// Translate a glob into an anchored regex pattern. `*` matches any run of
+/// characters (including `.` and `/`), `?` a single character and `[...]`/`[!...]`
+/// a character class; everything else is literal. Inside a class only ranges
+/// (`a-z`) and a leading `!` or `^` are special, and a `]` right after the opening
+/// bracket is literal. Regex class syntax such as `&&`, `--`, `~~` and nested
+/// brackets matches literally.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            '[' => {
+                let mut raw = String::from("[");
+                let mut class = String::new();
+
+                if let Some(&c @ ('!' | '^')) = chars.peek() {
+                    chars.next();
+                    raw.push(c);
+                    class.push('^');
+                }
+
+                let mut closed = false;
+                let mut first = true;
+
+                while let Some(c) = chars.next() {
+                    // "[]...]" and "[!]...]" start with a literal ']'
+                    if c == ']' && !first {
+                        closed = true;
+                        break;
+                    }
+
+                    first = false;
+
+                    raw.push(c);
+
+                    match c {
+                        // Doubled, these are regex class set operations
+                        '&' | '~' => class.push_str(&format!("\\{}", c)),
+                        '-' if chars.peek() == Some(&'-') => {
+                            chars.next();
+                            raw.push('-');
+                            class.push_str("\\-\\-");
+                        }
+                        '\\' | '[' | ']' | '^' => class.push_str(&format!("\\{}", c)),
+                        c => class.push(c),
+                    }
+                }
+
+                if closed {
+                    pattern.push('[');
+                    pattern.push_str(&class);
+                    pattern.push(']');
+                } else {
+                    // Unterminated class, treat the bracket literally
+                    pattern.push_str(&regex::escape(&raw));
+                }
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    pattern.push('$');
+    pattern
+}
+
+pub fn glob(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "glob requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    Ok(cached_regex(&glob_to_regex(&request.args[0]))?.is_match(field.str()))
+}
+
+// Args select which anomalies to look for; no args checks all of them:
+// "leading", "trailing", "double_space", "tab", "trailing_newline"
+pub fn whitespace_anomaly(request: &Request, field: Value) -> Result<bool, CustomError> {
+    const ALL_ANOMALIES: [&str; 5] = ["leading", "trailing", "double_space", "tab", "trailing_newline"];
+
+    let value = field.str();
+
+    let anomalies: Vec<&str> = if request.args.is_empty() {
+        ALL_ANOMALIES.to_vec()
+    } else {
+        request.args.iter().map(|a| a.as_str()).collect()
+    };
+
+    for anomaly in anomalies {
+        let found = match anomaly {
+            "leading" => value.starts_with(char::is_whitespace),
+            "trailing" => value.ends_with(char::is_whitespace),
+            "double_space" => value.contains("  "),
+            "tab" => value.contains('\t'),
+            "trailing_newline" => value.ends_with('\n'),
+            _ => {
+                return Err(CustomError::MatchError(format!(
+                    "unknown whitespace anomaly: {}",
+                    anomaly
+                )))
+            }
+        };
+
+        if found {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Parse an RFC3339 string or a unix timestamp (string or number) in seconds,
+/// milliseconds, microseconds or nanoseconds. The unit of a unix timestamp is
+/// inferred from its magnitude.
+pub fn parse_timestamp(field: &Value) -> Result<chrono::DateTime<chrono::Utc>, CustomError> {
+    let value = field.str().trim();
+
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&chrono::Utc));
+    }
+
+    let num: i64 = value
+        .parse()
+        .map_err(|_| CustomError::Error(format!("unable to parse timestamp: {}", value)))?;
+
+    let ts = match num.unsigned_abs() {
+        0..=99_999_999_999 => chrono::Utc.timestamp_opt(num, 0).single(),
+        100_000_000_000..=99_999_999_999_999 => chrono::Utc.timestamp_millis_opt(num).single(),
+        100_000_000_000_000..=99_999_999_999_999_999 => chrono::Utc.timestamp_micros(num).single(),
+        _ => Some(chrono::Utc.timestamp_nanos(num)),
+    };
+
+    ts.ok_or_else(|| CustomError::Error(format!("timestamp out of range: {}", value)))
+}
+
+// Characters that UTF-8 continuation bytes (0x80-0xBF) turn into when decoded as
+// Windows-1252 rather than Latin-1
+const CP1252_CONTINUATION_CHARS: &str = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+
+// Heuristic for double-decoded UTF-8, eg. "Ã©" where "é" was intended. Args are
+// additional literal signatures to look for.
+pub fn mojibake(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let value = field.str();
+
+    // Replacement char means something was already lost in a decode
+    if value.contains('\u{FFFD}') || request.args.iter().any(|sig| value.contains(sig.as_str()))
+    {
+        return Ok(true);
+    }
+
+    let is_continuation =
+        |c: char| ('\u{80}'..='\u{BF}').contains(&c) || CP1252_CONTINUATION_CHARS.contains(c);
+
+    let chars: Vec<char> = value.chars().collect();
+
+    for pair in chars.windows(2) {
+        let found = match pair[0] {
+            // Lead bytes of 2-byte sequences (U+0080 - U+07FF)
+            'Â' | 'Ã' | 'Å' | 'Æ' | 'Ð' | 'Ñ' | 'Ø' | 'Ù' => is_continuation(pair[1]),
+            // Lead byte of most punctuation (E2 80 xx), eg. "â€™" for "’"
+            'â' => pair[1] == '€',
+            // UTF-8 BOM decoded as Latin-1
+            'ï' => pair[1] == '»',
+            _ => false,
+        };
+
+        if found {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// data:[<mediatype>][;base64],<data> (RFC 2397). A base64 payload must decode.
+pub fn data_uri(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    let value = field.str().trim();
+
+    let rest = match value.get(..5) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &value[5..],
+        _ => return Ok(false),
+    };
+
+    let (meta, payload) = match rest.split_once(',') {
+        Some(parts) => parts,
+        None => return Ok(false),
+    };
+
+    // Only ";base64" marks the encoding. A bare "base64" is the media type, so
+    // "data:base64,..." is plain text with an invalid media type.
+    let mut params: Vec<&str> = meta.split(';').collect();
+    let is_base64 = params.len() > 1
+        && params.last().is_some_and(|p| p.eq_ignore_ascii_case("base64"));
+
+    if is_base64 {
+        params.pop();
+    }
+
+    // Media type is optional (text/plain by default, ie. "data:;base64,..." or
+    // "data:,..."), but when present must be type/subtype
+    let (media_type, params) = match params.split_first() {
+        Some((media_type, params)) => (*media_type, params),
+        None => ("", &[][..]),
+    };
+
+    if !media_type.is_empty() {
+        match media_type.split_once('/') {
+            Some((t, sub)) if is_token(t) && is_token(sub) => {}
+            _ => return Ok(false),
+        }
+    }
+
+    for param in params {
+        match param.split_once('=') {
+            Some((k, v)) if is_token(k) && !v.is_empty() => {}
+            _ => return Ok(false),
+        }
+    }
+
+    if is_base64 {
+        return Ok(base64::engine::general_purpose::STANDARD.decode(payload).is_ok());
+    }
+
+    Ok(!payload.contains(char::is_whitespace))
+}
+
+// RFC 2045 token: printable ASCII without spaces or tspecials
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&b))
+}
+
+// Plate formats by region, matched against the uppercased plate. Only current
+// issue formats are listed, so older or vanity plates need the loose default.
+const LICENSE_PLATE_FORMATS: &[(&str, &str)] = &[
+    // 2001 format: memory tag, age identifier, 3 random letters (no I or Q)
+    ("uk", r"^[A-HJ-PR-Y]{2}[0-9]{2} ?[A-HJ-PR-Z]{3}$"),
+    ("de", r"^[A-ZÄÖÜ]{1,3}[ -][A-Z]{1,2} ?[1-9][0-9]{0,3}[EH]?$"),
+    ("fr", r"^[A-HJ-NP-TV-Z]{2}-?[0-9]{3}-?[A-HJ-NP-TV-Z]{2}$"),
+    ("us_ca", r"^[1-9][A-Z]{3}[0-9]{3}$"),
+    ("us_ny", r"^[A-Z]{3}-?[0-9]{4}$"),
+];
+
+// 2-8 letters and digits, including at least one digit, in up to 3 groups split
+// by a single space or hyphen
+const LICENSE_PLATE_LOOSE: &str = r"^[A-Z0-9]{1,4}([ -]?[A-Z0-9]{1,4}){0,2}$";
+
+// Args: optional region from LICENSE_PLATE_FORMATS (ie. "uk", "us_ca"), otherwise
+// any plate-shaped string matches. Case-insensitive.
+pub fn license_plate(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() > 1 {
+        return Err(CustomError::Error(
+            "license_plate accepts at most 1 argument".to_string(),
+        ));
+    }
+
+    let plate = field.str().trim().to_uppercase();
+
+    let region = match request.args.first() {
+        Some(region) => region.to_ascii_lowercase(),
+        None => {
+            let alnum = plate.chars().filter(char::is_ascii_alphanumeric).count();
+
+            return Ok((2..=8).contains(&alnum)
+                && plate.chars().any(|c| c.is_ascii_digit())
+                && cached_regex(LICENSE_PLATE_LOOSE)?.is_match(&plate));
+        }
+    };
+
+    match LICENSE_PLATE_FORMATS.iter().find(|(r, _)| *r == region) {
+        Some((_, pattern)) => Ok(cached_regex(pattern)?.is_match(&plate)),
+        None => Err(CustomError::Error(format!(
+            "unknown license plate region '{}': must be one of {}",
+            request.args[0],
+            LICENSE_PLATE_FORMATS
+                .iter()
+                .map(|(r, _)| *r)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ))),
+    }
+}
+
+// Tab, line feed and carriage return
+const DEFAULT_ALLOWED_CONTROL_CHARS: &[char] = &['\t', '\n', '\r'];
+
+// Matches when the field holds a control character (C0, DEL or C1, per
+// char::is_control), ie. an embedded NUL, an escape sequence or a stray NEL,
+// other than the allowed ones.
+// Args: optional allowlist replacing the default of tab, LF and CR. Each arg is
+// the character itself or its code in hex (ie. "0x1b"); no args allows none.
+pub fn control_chars(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let allowed = if request.args.is_empty() {
+        DEFAULT_ALLOWED_CONTROL_CHARS.to_vec()
+    } else {
+        request
+            .args
+            .iter()
+            .map(|arg| control_char_arg(arg))
+            .collect::<Result<Vec<char>, CustomError>>()?
+    };
+
+    Ok(field
+        .str()
+        .chars()
+        .any(|c| c.is_control() && !allowed.contains(&c)))
+}
+
+fn control_char_arg(arg: &str) -> Result<char, CustomError> {
+    let c = match arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+        Some(hex) if !hex.is_empty() => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        _ => {
+            let mut chars = arg.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }
+    };
+
+    match c {
+        Some(c) if c.is_control() => Ok(c),
+        _ => Err(CustomError::InvalidArgument(format!(
+            "'{}' is not a control character",
+            arg.escape_debug()
+        ))),
+    }
+}
+
+// Abbreviated (ie. `git rev-parse --short`) or full SHA-1 object names, or full
+// SHA-256 ones. Args: optional minimum length of an abbreviated name, 7 by default
+// as git itself uses; values between 41 and 63 hex digits never match.
+pub fn git_sha(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let min_len = match request.args.first() {
+        Some(arg) => match arg.parse::<usize>() {
+            Ok(n) if (4..=40).contains(&n) => n,
+            _ => {
+                return Err(CustomError::InvalidArgument(format!(
+                    "git_sha minimum length must be between 4 and 40, got '{}'",
+                    arg
+                )))
+            }
+        },
+        None => 7,
+    };
+
+    let sha = field.str();
+
+    Ok(((min_len..=40).contains(&sha.len()) || sha.len() == 64)
+        && sha.chars().all(|c| c.is_ascii_hexdigit()))
+}
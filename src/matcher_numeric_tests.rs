@@ -174,3 +174,1192 @@ fn test_numeric() {
 
     crate::test_utils::run_tests(&test_cases);
 }
+
+#[test]
+fn test_numeric_interval() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[0,100]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "inclusive upper bound should contain boundary".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[0,100)".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "exclusive upper bound should not contain boundary".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[100,200]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "inclusive lower bound should contain boundary".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["(100,200]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "exclusive lower bound should not contain boundary".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["(1.5,100.1]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "float inclusive upper bound should contain boundary".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["(100, inf)".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "infinite upper bound".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["(-inf,100)".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "infinite lower bound with exclusive upper bound".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[,100]".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "empty lower bound should be unbounded".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[0;100]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "interval without comma should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[200,100]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "interval with lower > upper should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_numeric_leading_zeros() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "account_number".to_string(),
+                args: vec!["7".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "leading zeros should be allowed by default".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "account_number".to_string(),
+                args: vec!["7".to_string(), "reject_leading_zeros".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "leading zeros should error when rejected".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "account_number".to_string(),
+                args: vec!["reject_leading_zeros".to_string(), "0".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "leading zeros should error when rejected for range".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["0".to_string(), "reject_leading_zeros".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "a single zero is not a leading zero".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["100".to_string(), "reject_leading_zeros".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "value without leading zeros should match when rejecting".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_numeric_sign() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["positive".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 should be positive".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_negative".to_string(),
+                args: vec!["positive".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "-2.5 should not be positive".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["positive".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "zero should not be positive".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_negative".to_string(),
+                args: vec!["negative".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "-2.5 should be negative".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["negative".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "zero should not be negative".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["zero".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "zero should be zero".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["zero".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100.1 should not be zero".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["non-negative".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "zero should be non-negative".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["non-negative".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 should be non-negative".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_negative".to_string(),
+                args: vec!["non-negative".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "-2.5 should not be non-negative".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["non-positive".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "zero should be non-positive".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "object.number_as_string".to_string(),
+                args: vec!["positive".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "numeric strings should be parsed".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "object.field".to_string(),
+                args: vec!["positive".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-numeric string should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["sideways".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown sign should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec![],
+                negate: false,
+            },
+            expected: false,
+            text: "missing sign should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_matcher_tests(crate::matcher_numeric::sign, &test_cases);
+}
+
+#[test]
+fn test_numeric_error_kinds() {
+    use crate::error::CustomError;
+
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+    let det = crate::detective::Detective::new();
+
+    let request = |match_type: DetectiveType, path: &str, args: Vec<&str>| Request {
+        match_type,
+        data: sample_json,
+        path: path.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        negate: false,
+    };
+
+    // Rule authoring mistakes
+    let bad_args = vec![
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "number_int", vec!["not a number"]),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["1", "ten"]),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "number_int", vec!["[1,ten)"]),
+    ];
+
+    for req in bad_args {
+        let err = det.matches(&req).unwrap_err();
+        assert!(matches!(err, CustomError::InvalidArgument(_)), "{:?} for {:?}", err, req.args);
+    }
+
+    // Data that can't be compared numerically
+    let bad_fields = vec![
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO, "object.field", vec!["1"]),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE, "object.field", vec!["[0,10]"]),
+        request(DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN, "boolean_t", vec!["0"]),
+        request(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+            "account_number",
+            vec!["7", "reject_leading_zeros"],
+        ),
+    ];
+
+    for req in bad_fields {
+        let err = det.matches(&req).unwrap_err();
+        assert!(matches!(err, CustomError::InvalidField(_)), "{:?} for {}", err, req.path);
+    }
+
+    // A bad arg is reported as such even when the field is fine to compare
+    let err = crate::matcher_numeric::sign(
+        &request(DetectiveType::DETECTIVE_TYPE_UNKNOWN, "number_int", vec!["sideways"]),
+        gjson::parse("1"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, CustomError::InvalidArgument(_)));
+
+    // Numeric strings are still accepted
+    assert!(det
+        .matches(&request(
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+            "object.number_as_string",
+            vec!["1234"],
+        ))
+        .unwrap());
+}
+
+#[test]
+fn test_numeric_within_percent() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["95".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 should be within 10% of 95".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["110".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 should be within 10% of 110".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string(), "0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "exact value should be within 0%".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["120".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100 should not be within 10% of 120".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["80".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100.1 should not be within 10% of 80".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_negative".to_string(),
+                args: vec!["-2".to_string(), "25".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "-2.5 should be within 25% of -2".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["0".to_string(), "10".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "zero should be within any percent of a zero reference".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["0".to_string(), "1000".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-zero should never be within a percent of a zero reference".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string(), "-5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "negative percentage should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing percentage should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "object.field".to_string(),
+                args: vec!["100".to_string(), "5".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-numeric field should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_matcher_tests(crate::matcher_numeric::within_percent, &test_cases);
+}
+
+#[test]
+fn test_numeric_decimal() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        // f64 rounds both sides to the same value
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["0.3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "f64 considers 0.30000000000000001 equal to 0.3".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["0.3".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "decimal should not consider 0.30000000000000001 equal to 0.3".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["0.3".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal 0.30000000000000001 should be greater than 0.3".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "amount_large".to_string(),
+                args: vec!["9007199254740992".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "f64 cannot tell 2^53 + 1 from 2^53".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+                data: sample_json,
+                path: "amount_large".to_string(),
+                args: vec!["9007199254740992".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal 2^53 + 1 should be greater than 2^53".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN,
+                data: sample_json,
+                path: "amount_string".to_string(),
+                args: vec!["12345678901234567.02".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal string amounts should compare exactly".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_string".to_string(),
+                args: vec!["12345678901234567.02".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "f64 considers the two amounts equal".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "number_float".to_string(),
+                args: vec!["1.001e2".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal should accept exponents".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["-0.00".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal -0.00 should equal 0".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_LESS_THAN,
+                data: sample_json,
+                path: "number_negative".to_string(),
+                args: vec!["-2.25".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal -2.5 should be less than -2.25".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["0.3".to_string(), "0.30000000000000001".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal range should be inclusive".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_RANGE,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["[0,1)".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "decimal mode should reject intervals".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["abc".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "bad decimal arg should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_precise".to_string(),
+                args: vec!["0.01e-9223372036854775808".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "decimal arg with an out of range exponent should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "number_zero".to_string(),
+                args: vec!["0.00e-9223372036854775808".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal zero with any exponent should equal 0".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "boolean_t".to_string(),
+                args: vec!["1".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "non-number field should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_numeric_float_equality() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        // 0.1 + 0.2
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_sum".to_string(),
+                args: vec!["0.3".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "0.30000000000000004 should equal 0.3 within epsilon".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_sum".to_string(),
+                args: vec!["0.3".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "decimal mode should stay exact".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_sum".to_string(),
+                args: vec!["0.31".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "0.30000000000000004 should not equal 0.31".to_string(),
+            should_error: false,
+        },
+        // Large magnitudes scale the tolerance
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_huge".to_string(),
+                args: vec!["1e20".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "1.0000000000000002e20 should equal 1e20 within epsilon".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_huge".to_string(),
+                args: vec!["1.001e20".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "1.0000000000000002e20 should not equal 1.001e20".to_string(),
+            should_error: false,
+        },
+        // Integers compare exactly, even past f64 precision
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_large".to_string(),
+                args: vec!["9007199254740992".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "2^53 + 1 should not equal 2^53".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "amount_large".to_string(),
+                args: vec!["9007199254740993".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "2^53 + 1 should equal itself".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_NUMERIC_EQUAL_TO,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100.0".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "integer field should equal a float arg of the same value".to_string(),
+            should_error: false,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}
+
+#[test]
+fn test_numeric_expr() {
+    let data = r#"{
+        "total": 100.5,
+        "paid": "100.495",
+        "tolerance": 0.01,
+        "subtotal": 90,
+        "tax": 10.5,
+        "quantity": 4,
+        "unit_price": 22.5,
+        "zero": 0
+    }"#
+    .as_bytes()
+    .to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "total".to_string(),
+                args: vec!["-".to_string(), "paid".to_string(), "<=".to_string(), "0.01".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100.5 - 100.495 <= 0.01".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "total".to_string(),
+                args: vec!["-".to_string(), "paid".to_string(), "<=".to_string(), "0.001".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100.5 - 100.495 is over 0.001".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "total".to_string(),
+                args: vec!["-".to_string(), "paid".to_string(), "<=".to_string(), "tolerance".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "operand can be a path".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["+".to_string(), "tax".to_string(), "==".to_string(), "total".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "subtotal + tax == total".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["+".to_string(), "tax".to_string(), "!=".to_string(), "total".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "subtotal + tax is not != total".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "quantity".to_string(),
+                args: vec!["*".to_string(), "unit_price".to_string(), ">".to_string(), "subtotal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "4 * 22.5 is not > 90".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["/".to_string(), "quantity".to_string(), "gte".to_string(), "22.5".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "90 / 4 >= 22.5".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["/".to_string(), "zero".to_string(), "<".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "division by zero should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["%".to_string(), "quantity".to_string(), "<".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown operator should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["-".to_string(), "missing".to_string(), "<".to_string(), "1".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "missing second field should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: &data,
+                path: "subtotal".to_string(),
+                args: vec!["-".to_string(), "tax".to_string(), "<".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "3 args should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_matcher_tests(crate::matcher_numeric::expr, &test_cases);
+}
+
+#[test]
+fn test_numeric_between() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string(), "200".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 is on the lower bound".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["50".to_string(), "100".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "100 is on the upper bound".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["101".to_string(), "200".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100 is below 101..200".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["0".to_string(), "99.9".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "100 is above 0..99.9".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string(), "100".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "equal bounds include the value".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["99.99".to_string(), "100.01".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: true,
+            text: "decimal mode".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["200".to_string(), "100".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "lower bound over upper bound should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100.5".to_string(), "100.25".to_string(), "decimal".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "reversed decimal bounds should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["100".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "a single bound should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_UNKNOWN,
+                data: sample_json,
+                path: "number_int".to_string(),
+                args: vec!["[100,200]".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "interval notation is not accepted".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_matcher_tests(crate::matcher_numeric::between, &test_cases);
+}
@@ -1,13 +1,271 @@
 use gjson::Value;
-use crate::detective::Request;
+use crate::detective::{parse_number, MatcherFunc, Request};
 use crate::error::CustomError;
+use crate::hash;
 use idna::domain_to_ascii_strict;
+use lazy_static::lazy_static;
+use protos::sp_steps_detective::DetectiveType;
+use regex::Regex;
+use std::collections::HashMap;
 
-pub fn any(_request: &Request, _field: Value) -> Result<bool, CustomError> {
-    Err(CustomError::Error("not implemented".to_string()))
+// Detectors consulted by PII_ANY and Detective::pii_summary(), in priority order.
+// These are also the only detectors Detective::set_pattern_overrides() accepts.
+// PII_PHONE and PII_GEOLOCATION are implemented but left out, as digit runs and
+// coordinates in ordinary payloads would swamp a catch-all scan.
+pub(crate) const PII_DETECTORS: &[(DetectiveType, MatcherFunc)] = &[
+    (DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD, credit_card),
+    (DetectiveType::DETECTIVE_TYPE_PII_SSN, ssn),
+    (DetectiveType::DETECTIVE_TYPE_PII_EMAIL, email),
+];
+
+/// Patterns matched instead of the built-in detectors, see
+/// `Detective::set_pattern_overrides()`.
+pub type PatternOverrides = HashMap<DetectiveType, Regex>;
+
+// Optional arg: minimum confidence (0.0 - 1.0) a detector hit must have to count
+pub fn any(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(any_detail(request, field)?.is_some())
+}
+
+/// Like `any()`, but returns which detector hit (see `all_detail()`).
+pub fn any_detail(request: &Request, field: Value) -> Result<Option<DetectiveType>, CustomError> {
+    any_detail_with(request, field, &PatternOverrides::new())
+}
+
+/// Like `any_detail()`, but detectors in `overrides` match on their pattern.
+pub fn any_detail_with(
+    request: &Request,
+    field: Value,
+    overrides: &PatternOverrides,
+) -> Result<Option<DetectiveType>, CustomError> {
+    let min_confidence = match request.args.first() {
+        Some(arg) => parse_number(arg)?,
+        None => 0.0,
+    };
+
+    if !(0.0..=1.0).contains(&min_confidence) {
+        return Err(CustomError::Error(format!(
+            "minimum confidence must be between 0 and 1, got {}",
+            min_confidence
+        )));
+    }
+
+    all_detail_with(request, field, min_confidence, overrides)
+}
+
+/// Run every PII detector against `field`, returning true on the first hit
+/// whose confidence is at least `min_confidence`.
+pub fn all(request: &Request, field: Value, min_confidence: f64) -> Result<bool, CustomError> {
+    Ok(all_detail(request, field, min_confidence)?.is_some())
+}
+
+/// Like `all()`, but returns which detector hit first. Detectors run in
+/// `PII_DETECTORS` order: credit card, SSN, then email.
+pub fn all_detail(
+    request: &Request,
+    field: Value,
+    min_confidence: f64,
+) -> Result<Option<DetectiveType>, CustomError> {
+    all_detail_with(request, field, min_confidence, &PatternOverrides::new())
+}
+
+/// Like `all_detail()`, but detectors in `overrides` match on their pattern.
+pub fn all_detail_with(
+    request: &Request,
+    field: Value,
+    min_confidence: f64,
+    overrides: &PatternOverrides,
+) -> Result<Option<DetectiveType>, CustomError> {
+    for (detective_type, f) in PII_DETECTORS {
+        let hit = match overrides.get(detective_type) {
+            Some(re) => re.is_match(field.str()),
+            None => f(request, gjson::parse(field.json()))?,
+        };
+
+        if hit && confidence(*detective_type, &field) >= min_confidence {
+            return Ok(Some(*detective_type));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Why a PII detector did or didn't fire, from `Detective::diagnose_pii()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PiiDiagnosis {
+    pub match_type: DetectiveType,
+    pub matched: bool,
+    /// The first check that failed, ie. "16 digits found but Luhn failed", or the
+    /// detector's confidence on a hit.
+    pub reason: String,
+}
+
+/// Run every PII detector against `field`, explaining each outcome.
+pub fn diagnose(request: &Request, field: &Value) -> Vec<PiiDiagnosis> {
+    diagnose_with(request, field, &PatternOverrides::new())
+}
+
+/// Like `diagnose()`, but detectors in `overrides` match on their pattern and
+/// report whether it matched.
+pub fn diagnose_with(
+    request: &Request,
+    field: &Value,
+    overrides: &PatternOverrides,
+) -> Vec<PiiDiagnosis> {
+    PII_DETECTORS
+        .iter()
+        .map(|(match_type, _)| {
+            if let Some(re) = overrides.get(match_type) {
+                let matched = re.is_match(field.str());
+
+                return PiiDiagnosis {
+                    match_type: *match_type,
+                    matched,
+                    reason: if matched {
+                        "matched custom pattern".to_string()
+                    } else {
+                        "did not match custom pattern".to_string()
+                    },
+                };
+            }
+
+            let miss = match match_type {
+                DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD => credit_card_miss(request, field),
+                DetectiveType::DETECTIVE_TYPE_PII_SSN => ssn_miss(field),
+                DetectiveType::DETECTIVE_TYPE_PII_EMAIL => email_miss(request, field),
+                _ => Some("no diagnosis available".to_string()),
+            };
+
+            PiiDiagnosis {
+                match_type: *match_type,
+                matched: miss.is_none(),
+                reason: miss.unwrap_or_else(|| {
+                    format!("matched with confidence {}", confidence(*match_type, field))
+                }),
+            }
+        })
+        .collect()
+}
+
+/// How confident we are that a hit from the given detector is real PII.
+///
+/// Known test/placeholder values (see `is_test_value()`) always get a low confidence.
+pub fn confidence(detective_type: DetectiveType, field: &Value) -> f64 {
+    if is_test_value(detective_type, field) {
+        return 0.1;
+    }
+
+    match detective_type {
+        // A Luhn-valid number from an unknown issuer is often just an ID that
+        // happens to checksum
+        DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD => {
+            if card_brand(field.str()).is_some() {
+                0.9
+            } else {
+                0.5
+            }
+        }
+        DetectiveType::DETECTIVE_TYPE_PII_SSN => 0.8,
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL => 0.9,
+        _ => 0.0,
+    }
+}
+
+// Published test card numbers (payment processor docs) and placeholder emails
+const TEST_CARD_NUMBERS: &[&str] = &[
+    "4111111111111111",
+    "4242424242424242",
+    "4012888888881881",
+    "4000056655665556",
+    "5555555555554444",
+    "5105105105105100",
+    "2223003122003222",
+    "378282246310005",
+    "371449635398431",
+    "6011111111111117",
+    "6011000990139424",
+    "30569309025904",
+    "38520000023237",
+    "3530111333300000",
+    "3566002020360505",
+    "6200000000000005",
+];
+
+const TEST_EMAIL_DOMAINS: &[&str] = &["example.com", "example.org", "example.net", "test.com"];
+
+const TEST_EMAIL_LOCAL_PARTS: &[&str] = &["test", "foo", "user", "john.doe", "jane.doe"];
+
+/// True for well-known test/placeholder values that shouldn't be treated as real PII,
+/// such as processor test cards ("4111111111111111") or "test@test.com".
+pub fn is_test_value(detective_type: DetectiveType, field: &Value) -> bool {
+    match detective_type {
+        DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD => {
+            let num = field.str().trim().replace(['-', ' '], "");
+            TEST_CARD_NUMBERS.contains(&num.as_str())
+        }
+        DetectiveType::DETECTIVE_TYPE_PII_EMAIL => {
+            let email = field.str().trim().to_ascii_lowercase();
+
+            match email.split_once('@') {
+                Some((local, domain)) => {
+                    TEST_EMAIL_DOMAINS.contains(&domain)
+                        || (TEST_EMAIL_LOCAL_PARTS.contains(&local) && domain.starts_with("test."))
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Option arg: known test values (ie. 4111 1111 1111 1111, user@example.com) don't match.
+pub const IGNORE_TEST_VALUES: &str = "ignore_test_values";
+
+fn ignore_test_values(request: &Request) -> bool {
+    request.args.iter().any(|a| a == IGNORE_TEST_VALUES)
+}
+
+/// Identify the card brand from the issuer prefix (IIN) and length of a card number.
+/// Separators are ignored; no checksum validation is done here.
+pub fn card_brand(number: &str) -> Option<&'static str> {
+    let digits: String = number.chars().filter(|c| c.is_ascii_digit()).collect();
+    let len = digits.len();
+    let prefix = |n: usize| digits.get(..n).and_then(|p| p.parse::<u32>().ok()).unwrap_or(0);
+
+    match (prefix(1), prefix(2), prefix(3), prefix(4)) {
+        (4, _, _, _) if [13, 16, 19].contains(&len) => Some("visa"),
+        (_, 51..=55, _, _) | (_, _, _, 2221..=2720) if len == 16 => Some("mastercard"),
+        (_, 34 | 37, _, _) if len == 15 => Some("amex"),
+        (_, 65, _, _) | (_, _, 644..=649, _) | (_, _, _, 6011) if (16..=19).contains(&len) => {
+            Some("discover")
+        }
+        (_, 36 | 38, _, _) | (_, _, 300..=305, _) if (14..=19).contains(&len) => {
+            Some("diners_club")
+        }
+        (_, _, _, 3528..=3589) if (16..=19).contains(&len) => Some("jcb"),
+        (_, 62, _, _) if (16..=19).contains(&len) => Some("unionpay"),
+        _ => None,
+    }
+}
+
+/// Option arg for PII_CREDIT_CARD: any 13-19 digit number matches, without
+/// checking the Luhn checksum.
+pub const SKIP_LUHN: &str = "skip_luhn";
+
+// Optional args "ignore_test_values": known test card numbers don't match, and
+// "skip_luhn": don't require a valid Luhn checksum
+pub fn credit_card(request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(credit_card_miss(request, &field).is_none())
 }
 
-pub fn credit_card(_request: &Request, field: Value) -> Result<bool, CustomError> {
+// Why `field` isn't a credit card number, or None if it is
+fn credit_card_miss(request: &Request, field: &Value) -> Option<String> {
+    if ignore_test_values(request)
+        && is_test_value(DetectiveType::DETECTIVE_TYPE_PII_CREDIT_CARD, field)
+    {
+        return Some("known test card number ignored".to_string());
+    }
+
     let num = field.str().trim().replace(['-', ' '], "");
 
     // Convert the card number string to a vector of digits
@@ -18,7 +276,11 @@ pub fn credit_card(_request: &Request, field: Value) -> Result<bool, CustomError
 
     // Check if the number of digits is valid for a credit card
     if digits.len() < 13 || digits.len() > 19 {
-        return Ok(false);
+        return Some(format!("{} digits found, expected 13-19", digits.len()));
+    }
+
+    if request.args.iter().any(|a| a == SKIP_LUHN) {
+        return None;
     }
 
     // Implement the Luhn algorithm
@@ -40,36 +302,77 @@ pub fn credit_card(_request: &Request, field: Value) -> Result<bool, CustomError
         double = !double;
     }
 
-    let res = sum % 10 == 0;
+    if sum % 10 != 0 {
+        return Some(format!("{} digits found but Luhn failed", digits.len()));
+    }
 
-    Ok(res)
+    None
 }
 
-pub fn ssn(_request: &Request, _field: Value) -> Result<bool, CustomError> {
-    Err(CustomError::Error("not implemented".to_string()))
+lazy_static! {
+    static ref SSN_RE: Regex = Regex::new(r"^(\d{3})[- ](\d{2})[- ](\d{4})$").unwrap();
+}
+
+// US social security number written as AAA-GG-SSSS (or with spaces). Bare runs of
+// 9 digits are too common to be treated as an SSN.
+pub fn ssn(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(ssn_miss(&field).is_none())
+}
+
+fn ssn_miss(field: &Value) -> Option<String> {
+    let caps = match SSN_RE.captures(field.str().trim()) {
+        Some(caps) => caps,
+        None => return Some("not formatted as AAA-GG-SSSS".to_string()),
+    };
+
+    // Never issued: area 000, 666 or 900-999, group 00 and serial 0000
+    let area: u32 = caps[1].parse().unwrap_or_default();
+
+    if area == 0 || area == 666 || area >= 900 {
+        return Some(format!("area number {} is never issued", &caps[1]));
+    }
+
+    if &caps[2] == "00" {
+        return Some("group number 00 is never issued".to_string());
+    }
+
+    if &caps[3] == "0000" {
+        return Some("serial number 0000 is never issued".to_string());
+    }
+
+    None
 }
 
-pub fn email(_request: &Request, email: Value) -> Result<bool, CustomError> {
+// Optional arg "ignore_test_values": placeholder addresses don't match
+pub fn email(request: &Request, email: Value) -> Result<bool, CustomError> {
+    Ok(email_miss(request, &email).is_none())
+}
 
+fn email_miss(request: &Request, email: &Value) -> Option<String> {
+    if ignore_test_values(request)
+        && is_test_value(DetectiveType::DETECTIVE_TYPE_PII_EMAIL, email)
+    {
+        return Some("known test address ignored".to_string());
+    }
 
     // Split the email address into local part and domain part
     let parts: Vec<&str> = email.str().trim().split('@').collect();
 
     // Ensure there are exactly two parts (local and domain)
     if parts.len() != 2 {
-        return Ok(false);
+        return Some(format!("expected exactly one '@', found {}", parts.len() - 1));
     }
 
     // Check if local part is not empty
     let local_part = parts[0];
     if local_part.is_empty() {
-        return Ok(false);
+        return Some("local part is empty".to_string());
     }
 
     // Check if domain part is not empty
     let domain_part = parts[1];
     if domain_part.is_empty() {
-        return Ok(false);
+        return Some("domain is empty".to_string());
     }
 
     // Check for valid characters in local part
@@ -78,7 +381,7 @@ pub fn email(_request: &Request, email: Value) -> Result<bool, CustomError> {
             if char::from_u32(c as u32).is_some() {
                 continue;
             } else {
-                return Ok(false);
+                return Some(format!("invalid character '{}' in local part", c));
             }
         }
     }
@@ -86,27 +389,27 @@ pub fn email(_request: &Request, email: Value) -> Result<bool, CustomError> {
     // Support IDNA
     let decoded_domain = match domain_to_ascii_strict(domain_part) {
         Ok(ascii_domain) => ascii_domain,
-        Err(_) => return Ok(false),
+        Err(_) => return Some(format!("domain '{}' is not a valid IDNA name", domain_part)),
     };
 
     // Check for valid characters in the encoded domain part
     for c in decoded_domain.chars() {
         if !(c.is_ascii_alphanumeric() || c == '.' || c == '-') {
-            return Ok(false);
+            return Some(format!("invalid character '{}' in domain", c));
         }
     }
 
     // Check if the encoded domain part contains at least one dot
     if !decoded_domain.contains('.') {
-        return Ok(false);
+        return Some(format!("domain '{}' has no '.'", domain_part));
     }
 
     // Dot can't be first or last character
     if decoded_domain.starts_with('.') || decoded_domain.ends_with('.') {
-        return Ok(false);
+        return Some(format!("domain '{}' starts or ends with '.'", domain_part));
     }
 
-    Ok(true)
+    None
 }
 
 pub fn drivers_license(_request: &Request, _field: Value) -> Result<bool, CustomError> {
@@ -121,8 +424,548 @@ pub fn vin_number(_request: &Request, _field: Value) -> Result<bool, CustomError
     Err(CustomError::Error("not implemented".to_string()))
 }
 
-pub fn phone(_request: &Request, _field: Value) -> Result<bool, CustomError> {
-    Err(CustomError::Error("not implemented".to_string()))
+// ITU-T E.164 assigned country calling codes
+const CALLING_CODES: &[u16] = &[
+    1, 7, 20, 27, 30, 31, 32, 33, 34, 36, 39, 40, 41, 43, 44, 45, 46, 47, 48, 49, 51, 52, 53, 54,
+    55, 56, 57, 58, 60, 61, 62, 63, 64, 65, 66, 81, 82, 84, 86, 90, 91, 92, 93, 94, 95, 98, 211,
+    212, 213, 216, 218, 220, 221, 222, 223, 224, 225, 226, 227, 228, 229, 230, 231, 232, 233, 234,
+    235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252, 253,
+    254, 255, 256, 257, 258, 260, 261, 262, 263, 264, 265, 266, 267, 268, 269, 290, 291, 297, 298,
+    299, 350, 351, 352, 353, 354, 355, 356, 357, 358, 359, 370, 371, 372, 373, 374, 375, 376, 377,
+    378, 380, 381, 382, 383, 385, 386, 387, 389, 420, 421, 423, 500, 501, 502, 503, 504, 505, 506,
+    507, 508, 509, 590, 591, 592, 593, 594, 595, 596, 597, 598, 599, 670, 672, 673, 674, 675, 676,
+    677, 678, 679, 680, 681, 682, 683, 685, 686, 687, 688, 689, 690, 691, 692, 800, 808, 850, 852,
+    853, 855, 856, 870, 878, 880, 881, 882, 883, 886, 888, 960, 961, 962, 963, 964, 965, 966, 967,
+    968, 970, 971, 972, 973, 974, 975, 976, 977, 979, 992, 993, 994, 995, 996, 997, 998,
+];
+
+/// Length of the country calling code at the start of `digits` (an E.164 number
+/// without the '+'), or None if it doesn't start with an assigned code.
+pub fn calling_code_len(digits: &str) -> Option<usize> {
+    (1..=3).find(|&len| {
+        digits
+            .get(..len)
+            .and_then(|code| code.parse::<u16>().ok())
+            .is_some_and(|code| CALLING_CODES.contains(&code))
+    })
+}
+
+struct PhoneCountry {
+    iso: &'static str,
+    calling_code: &'static str,
+    // Dialed before the national significant number in national format, ie. the
+    // 0 in "020 7946 0958"
+    trunk_prefix: &'static str,
+    trunk_optional: bool,
+    // Digits in the national significant number
+    min_len: usize,
+    max_len: usize,
+}
+
+// National formats of the countries a phone number can be hinted to be from
+const PHONE_COUNTRIES: &[PhoneCountry] = &[
+    PhoneCountry { iso: "US", calling_code: "1", trunk_prefix: "1", trunk_optional: true, min_len: 10, max_len: 10 },
+    PhoneCountry { iso: "CA", calling_code: "1", trunk_prefix: "1", trunk_optional: true, min_len: 10, max_len: 10 },
+    PhoneCountry { iso: "GB", calling_code: "44", trunk_prefix: "0", trunk_optional: false, min_len: 9, max_len: 10 },
+    PhoneCountry { iso: "IE", calling_code: "353", trunk_prefix: "0", trunk_optional: false, min_len: 7, max_len: 9 },
+    PhoneCountry { iso: "DE", calling_code: "49", trunk_prefix: "0", trunk_optional: false, min_len: 6, max_len: 13 },
+    PhoneCountry { iso: "FR", calling_code: "33", trunk_prefix: "0", trunk_optional: false, min_len: 9, max_len: 9 },
+    PhoneCountry { iso: "NL", calling_code: "31", trunk_prefix: "0", trunk_optional: false, min_len: 9, max_len: 9 },
+    PhoneCountry { iso: "ES", calling_code: "34", trunk_prefix: "", trunk_optional: false, min_len: 9, max_len: 9 },
+    // Italian numbers keep their leading 0 internationally
+    PhoneCountry { iso: "IT", calling_code: "39", trunk_prefix: "", trunk_optional: false, min_len: 6, max_len: 11 },
+    PhoneCountry { iso: "AU", calling_code: "61", trunk_prefix: "0", trunk_optional: false, min_len: 9, max_len: 9 },
+    PhoneCountry { iso: "JP", calling_code: "81", trunk_prefix: "0", trunk_optional: false, min_len: 9, max_len: 10 },
+    PhoneCountry { iso: "CN", calling_code: "86", trunk_prefix: "0", trunk_optional: false, min_len: 10, max_len: 11 },
+    PhoneCountry { iso: "IN", calling_code: "91", trunk_prefix: "0", trunk_optional: true, min_len: 10, max_len: 10 },
+    PhoneCountry { iso: "SG", calling_code: "65", trunk_prefix: "", trunk_optional: false, min_len: 8, max_len: 8 },
+    PhoneCountry { iso: "BR", calling_code: "55", trunk_prefix: "0", trunk_optional: true, min_len: 10, max_len: 11 },
+];
+
+// Digits with common separators (space, '-', '.', parens) and an optional leading '+'.
+// International numbers must start with an assigned calling code and are capped at
+// 15 digits per E.164. Without a country, only international numbers match:
+// national numbers can't be told apart from order IDs, timestamps and the like.
+// Optional arg: ISO 3166 country (ie. "GB") whose national format and number
+// lengths numbers are checked against, including international numbers with its
+// calling code.
+pub fn phone(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let country = match request.args.first() {
+        Some(iso) => Some(
+            PHONE_COUNTRIES
+                .iter()
+                .find(|c| c.iso.eq_ignore_ascii_case(iso))
+                .ok_or_else(|| {
+                    CustomError::InvalidArgument(format!(
+                        "unsupported phone country '{}': must be one of {}",
+                        iso,
+                        PHONE_COUNTRIES.iter().map(|c| c.iso).collect::<Vec<&str>>().join(", ")
+                    ))
+                })?,
+        ),
+        None => None,
+    };
+
+    let value = field.str().trim();
+    let (international, number) = match value.strip_prefix('+') {
+        Some(number) => (true, number),
+        None => (false, value),
+    };
+
+    if number.is_empty()
+        || !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'))
+    {
+        return Ok(false);
+    }
+
+    let digits: String = number.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    if digits.len() > 15 {
+        return Ok(false);
+    }
+
+    if international {
+        if calling_code_len(&digits).is_none() {
+            return Ok(false);
+        }
+
+        return Ok(match country {
+            Some(c) if digits.starts_with(c.calling_code) => {
+                is_national_number(c, &digits[c.calling_code.len()..])
+            }
+            _ => digits.len() >= 7,
+        });
+    }
+
+    let country = match country {
+        Some(country) => country,
+        None => return Ok(false),
+    };
+
+    match digits.strip_prefix(country.trunk_prefix) {
+        Some(rest) if is_national_number(country, rest) => Ok(true),
+        _ if country.trunk_optional || country.trunk_prefix.is_empty() => {
+            Ok(is_national_number(country, &digits))
+        }
+        _ => Ok(false),
+    }
+}
+
+fn is_national_number(country: &PhoneCountry, digits: &str) -> bool {
+    (country.min_len..=country.max_len).contains(&digits.len())
+}
+
+/// The parts of a national ID that get validated: the embedded birth date and
+/// whether the check digit(s) hold.
+struct NationalId {
+    year: i32,
+    month: u32,
+    day: u32,
+    checksum_valid: bool,
+}
+
+// Returns None when the value isn't shaped like the country's ID at all. The
+// date is range checked by national_id().
+type NationalIdParser = fn(&str) -> Option<NationalId>;
+
+// (ISO 3166-1 alpha-2 country, parser). Add countries here.
+const NATIONAL_ID_FORMATS: &[(&str, NationalIdParser)] = &[("EE", estonian_id)];
+
+// Estonian isikukood: GYYMMDDSSSC. G encodes sex and century (1/2 = 1800s,
+// 3/4 = 1900s, 5/6 = 2000s, 7/8 = 2100s), SSS is a serial and C a mod 11 check
+// digit over two weight sets.
+fn estonian_id(value: &str) -> Option<NationalId> {
+    if value.len() != 11 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits: Vec<u32> = value.bytes().map(|b| (b - b'0') as u32).collect();
+    let number = |range: std::ops::Range<usize>| digits[range].iter().fold(0, |n, d| n * 10 + d);
+
+    let century = match digits[0] {
+        1 | 2 => 1800,
+        3 | 4 => 1900,
+        5 | 6 => 2000,
+        7 | 8 => 2100,
+        _ => return None,
+    };
+
+    let weighted = |weights: [u32; 10]| {
+        digits.iter().zip(weights).map(|(d, w)| d * w).sum::<u32>() % 11
+    };
+
+    let check = match weighted([1, 2, 3, 4, 5, 6, 7, 8, 9, 1]) {
+        10 => match weighted([3, 4, 5, 6, 7, 8, 9, 1, 2, 3]) {
+            10 => 0,
+            check => check,
+        },
+        check => check,
+    };
+
+    Some(NationalId {
+        year: century + number(1..3) as i32,
+        month: number(3..5),
+        day: number(5..7),
+        checksum_valid: check == digits[10],
+    })
+}
+
+// National ID with an embedded birth date. Takes the country code as the only
+// arg (supported: see NATIONAL_ID_FORMATS). Matches when the value has the
+// country's format, the check digit holds and the date exists.
+pub fn national_id(request: &Request, field: Value) -> Result<bool, CustomError> {
+    if request.args.len() != 1 {
+        return Err(CustomError::Error(
+            "national_id requires exactly 1 argument: the country code".to_string(),
+        ));
+    }
+
+    let country = request.args[0].to_ascii_uppercase();
+
+    let parse = NATIONAL_ID_FORMATS
+        .iter()
+        .find(|(c, _)| *c == country)
+        .map(|(_, parse)| parse)
+        .ok_or_else(|| {
+            CustomError::InvalidArgument(format!("unsupported national id country '{}'", country))
+        })?;
+
+    let id = match parse(field.str().trim()) {
+        Some(id) => id,
+        None => return Ok(false),
+    };
+
+    Ok(id.checksum_valid && chrono::NaiveDate::from_ymd_opt(id.year, id.month, id.day).is_some())
+}
+
+// (ISO 3166-1 alpha-2 country, IBAN length) per the SWIFT IBAN registry
+const IBAN_LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24), ("AE", 23), ("AL", 28), ("AT", 20), ("AZ", 28), ("BA", 20), ("BE", 16),
+    ("BG", 22), ("BH", 22), ("BI", 27), ("BR", 29), ("BY", 28), ("CH", 21), ("CR", 22),
+    ("CY", 28), ("CZ", 24), ("DE", 22), ("DJ", 27), ("DK", 18), ("DO", 28), ("EE", 20),
+    ("EG", 29), ("ES", 24), ("FI", 18), ("FK", 18), ("FO", 18), ("FR", 27), ("GB", 22),
+    ("GE", 22), ("GI", 23), ("GL", 18), ("GR", 27), ("GT", 28), ("HR", 21), ("HU", 28),
+    ("IE", 22), ("IL", 23), ("IQ", 23), ("IS", 26), ("IT", 27), ("JO", 30), ("KW", 30),
+    ("KZ", 20), ("LB", 28), ("LC", 32), ("LI", 21), ("LT", 20), ("LU", 20), ("LV", 21),
+    ("LY", 25), ("MC", 27), ("MD", 24), ("ME", 22), ("MK", 19), ("MN", 20), ("MR", 27),
+    ("MT", 31), ("MU", 30), ("NI", 28), ("NL", 18), ("NO", 15), ("OM", 23), ("PK", 24),
+    ("PL", 28), ("PS", 29), ("PT", 25), ("QA", 29), ("RO", 24), ("RS", 22), ("RU", 33),
+    ("SA", 24), ("SC", 31), ("SD", 18), ("SE", 24), ("SI", 19), ("SK", 24), ("SM", 27),
+    ("SO", 23), ("ST", 25), ("SV", 28), ("TL", 23), ("TN", 24), ("TR", 26), ("UA", 29),
+    ("VA", 22), ("VG", 24), ("XK", 20), ("YE", 30),
+];
+
+/// Whether `value` is an IBAN with a valid ISO 13616 mod 97 checksum. Whitespace
+/// is ignored (ie. the printed "DE89 3704 ...") and letters may be lowercase. The
+/// length must match the registry length for the country.
+pub fn is_valid_iban(value: &str) -> bool {
+    let iban: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if !iban.is_ascii() || iban.len() < 4 {
+        return false;
+    }
+
+    let (country, check_digits) = (&iban[..2], &iban[2..4]);
+
+    let length_valid = IBAN_LENGTHS
+        .iter()
+        .any(|(c, len)| *c == country && *len == iban.len());
+
+    if !length_valid
+        || !check_digits.bytes().all(|b| b.is_ascii_digit())
+        || !iban.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        return false;
+    }
+
+    // Move the first 4 characters to the end and read letters as 10-35. The
+    // remainder is carried digit by digit as the number doesn't fit in a u128.
+    let remainder = iban[4..].chars().chain(iban[..4].chars()).fold(0u32, |acc, c| {
+        let value = c.to_digit(36).unwrap_or(0);
+
+        if value < 10 {
+            (acc * 10 + value) % 97
+        } else {
+            (acc * 100 + value) % 97
+        }
+    });
+
+    remainder == 1
+}
+
+// International Bank Account Number, see is_valid_iban(). Only checksum valid
+// IBANs match, so random alphanumerics with a country prefix don't.
+pub fn iban(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(is_valid_iban(field.str()))
+}
+
+/// Option arg for uk_bank: also check a sort code and account number pair
+/// against the few VocaLink weight table rows embedded in UK_MODULUS_WEIGHTS.
+/// This is not a full modulus check: pairs with sort codes outside those rows
+/// never match.
+pub const EMBEDDED_MODULUS_CHECK: &str = "embedded_modulus_check";
+
+#[derive(Clone, Copy)]
+enum ModulusMethod {
+    Mod10,
+    Mod11,
+}
+
+// (first sort code, last sort code, method, weights for the 6 sort code and 8
+// account digits). Only two rows of VocaLink's published valacdos table, which
+// has about a thousand; add rows from it as needed, and always every row for a
+// sort code - a sort code with only some of its rows would be partially checked.
+// Exceptions are not implemented.
+const UK_MODULUS_WEIGHTS: &[(u32, u32, ModulusMethod, [u32; 14])] = &[
+    (89000, 89999, ModulusMethod::Mod10, [0, 0, 0, 0, 0, 0, 7, 1, 3, 7, 1, 3, 7, 1]),
+    (107999, 107999, ModulusMethod::Mod11, [0, 0, 0, 0, 0, 0, 8, 7, 6, 5, 4, 3, 2, 1]),
+];
+
+// 6 digits, either plain or as XX-XX-XX
+fn uk_sort_code(value: &str) -> Option<String> {
+    let digits = match value.len() {
+        6 => value.to_string(),
+        8 if value.as_bytes()[2] == b'-' && value.as_bytes()[5] == b'-' => value.replace('-', ""),
+        _ => return None,
+    };
+
+    if digits.len() == 6 && digits.bytes().all(|b| b.is_ascii_digit()) {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+fn is_uk_account_number(value: &str) -> bool {
+    value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// VocaLink modulus check of a 6 digit sort code and 8 digit account number
+/// against the rows embedded in UK_MODULUS_WEIGHTS. Every row covering the sort
+/// code must pass. Returns None when no embedded row covers the sort code (or the
+/// input isn't 6 + 8 digits), ie. the pair couldn't be checked.
+pub fn uk_modulus_check(sort_code: &str, account: &str) -> Option<bool> {
+    let sort_code_number: u32 = sort_code.parse().ok()?;
+
+    let digits: Vec<u32> = sort_code
+        .chars()
+        .chain(account.chars())
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+
+    if digits.len() != 14 {
+        return None;
+    }
+
+    let rows: Vec<_> = UK_MODULUS_WEIGHTS
+        .iter()
+        .filter(|(first, last, _, _)| (*first..=*last).contains(&sort_code_number))
+        .collect();
+
+    if rows.is_empty() {
+        return None;
+    }
+
+    Some(rows.iter().all(|(_, _, method, weights)| {
+        let sum: u32 = digits.iter().zip(weights).map(|(d, w)| d * w).sum();
+
+        match method {
+            ModulusMethod::Mod10 => sum.is_multiple_of(10),
+            ModulusMethod::Mod11 => sum.is_multiple_of(11),
+        }
+    }))
+}
+
+// UK sort code (123456 or 12-34-56), 8 digit account number, or a sort code and
+// account number separated by whitespace, ',' or '/'. With
+// "embedded_modulus_check", a pair must also pass uk_modulus_check(); pairs it
+// can't check don't match.
+pub fn uk_bank(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let modulus_check = request.args.iter().any(|a| a == EMBEDDED_MODULUS_CHECK);
+
+    let parts: Vec<&str> = field
+        .str()
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    match parts.as_slice() {
+        [value] => Ok(uk_sort_code(value).is_some() || is_uk_account_number(value)),
+        [sort_code, account] => match uk_sort_code(sort_code) {
+            Some(sort_code) if is_uk_account_number(account) => {
+                Ok(!modulus_check || uk_modulus_check(&sort_code, account) == Some(true))
+            }
+            _ => Ok(false),
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Option arg for crypto_address: require a valid EIP-55 checksum on mixed case
+/// Ethereum addresses. All lowercase or all uppercase addresses carry no checksum
+/// and still match.
+pub const EIP55: &str = "eip55";
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn base58_decode(value: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    for c in value.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    // Each leading '1' is a leading zero byte
+    let zeros = value.bytes().take_while(|&c| c == b'1').count();
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes);
+
+    Some(decoded)
+}
+
+/// Bitcoin P2PKH ('1...') or P2SH ('3...') address with a valid base58check
+/// checksum: the first 4 bytes of a double SHA-256 over the version and hash.
+pub fn is_valid_base58_address(value: &str) -> bool {
+    let decoded = match base58_decode(value) {
+        Some(decoded) if decoded.len() == 25 => decoded,
+        _ => return false,
+    };
+
+    let (payload, checksum) = decoded.split_at(21);
+
+    // Version 0x00 is P2PKH and 0x05 P2SH
+    matches!(payload[0], 0x00 | 0x05) && hash::sha256(&hash::sha256(payload))[..4] == *checksum
+}
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATORS: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    values.iter().fold(1, |chk, &v| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x1ffffff) << 5) ^ v as u32;
+
+        GENERATORS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+/// Bitcoin segwit address (`bc1...`) per BIP-173/BIP-350: bech32 checksum for
+/// witness version 0, bech32m for versions 1-16 (ie. taproot), and a witness
+/// program of a valid length for the version.
+pub fn is_valid_bech32_address(value: &str) -> bool {
+    if value.len() > 90 || (value.to_lowercase() != value && value.to_uppercase() != value) {
+        return false;
+    }
+
+    let value = value.to_lowercase();
+
+    let data: Vec<u8> = match value.strip_prefix("bc1") {
+        Some(data) if data.len() >= 7 => {
+            match data
+                .bytes()
+                .map(|c| BECH32_CHARSET.iter().position(|&a| a == c).map(|p| p as u8))
+                .collect::<Option<Vec<u8>>>()
+            {
+                Some(data) => data,
+                None => return false,
+            }
+        }
+        _ => return false,
+    };
+
+    // Expanded human readable part "bc"
+    let mut values = vec![3, 3, 0, 2, 3];
+    values.extend(&data);
+
+    let version = data[0];
+    let expected = if version == 0 { BECH32_CONST } else { BECH32M_CONST };
+
+    if version > 16 || bech32_polymod(&values) != expected {
+        return false;
+    }
+
+    // Regroup the 5 bit words between the version and checksum into bytes
+    let mut program = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+
+    for &word in &data[1..data.len() - 6] {
+        acc = (acc << 5) | word as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            program.push((acc >> bits) as u8);
+        }
+    }
+
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        return false;
+    }
+
+    match version {
+        0 => matches!(program.len(), 20 | 32),
+        _ => (2..=40).contains(&program.len()),
+    }
+}
+
+/// Ethereum address: `0x` and 40 hex digits. With `eip55`, mixed case addresses
+/// must carry a valid EIP-55 checksum, where a letter is uppercase when the
+/// matching nibble of the Keccak-256 of the lowercase address is 8 or more.
+pub fn is_valid_ethereum_address(value: &str, eip55: bool) -> bool {
+    let hex = match value.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => hex,
+        _ => return false,
+    };
+
+    let lowercase = hex.to_ascii_lowercase();
+
+    if !eip55 || hex == lowercase || hex == hex.to_ascii_uppercase() {
+        return true;
+    }
+
+    let digest = hash::keccak256(lowercase.as_bytes());
+
+    hex.bytes().enumerate().all(|(i, c)| {
+        let nibble = (digest[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+
+        !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+// Cryptocurrency wallet address: Bitcoin base58check (P2PKH/P2SH) or bech32 and
+// Ethereum. Bitcoin checksums are always validated; pass "eip55" to validate the
+// Ethereum mixed case checksum as well.
+pub fn crypto_address(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let eip55 = request.args.iter().any(|a| a == EIP55);
+    let value = field.str().trim();
+
+    Ok(is_valid_base58_address(value)
+        || is_valid_bech32_address(value)
+        || is_valid_ethereum_address(value, eip55))
 }
 
 // Intended to operate on the entire payload
@@ -149,9 +992,123 @@ pub fn signature(_request: &Request, _field: Value) -> Result<bool, CustomError>
     Err(CustomError::Error("not implemented".to_string()))
 }
 
-// Intended to operate on the entire payload
-pub fn geolocation(_request: &Request, _field: Value) -> Result<bool, CustomError> {
-    Err(CustomError::Error("not implemented".to_string()))
+/// Option arg for PII_GEOLOCATION: also accept degrees-minutes-seconds
+/// coordinates, ie. `40°26'46"N 79°58'56"W`.
+pub const DMS: &str = "dms";
+
+lazy_static! {
+    // 40°26'46"N, 40°26′46.5″N, 40°26'N. Prime/double prime marks are accepted
+    // as well as quotes.
+    static ref DMS_RE: Regex = Regex::new(
+        r#"^(\d{1,3})°\s*(?:(\d{1,2})['′]\s*(?:(\d{1,2}(?:\.\d+)?)(?:["″]|''))?)?\s*([NSEWnsew])$"#
+    )
+    .unwrap();
+}
+
+/// Parse a single degrees-minutes-seconds coordinate into signed decimal degrees
+/// (south and west are negative) and its hemisphere letter, uppercased. Returns
+/// None when the value isn't DMS or is out of range: minutes and seconds must be
+/// below 60, latitudes (N/S) at most 90° and longitudes (E/W) at most 180°.
+pub fn parse_dms(input: &str) -> Option<(f64, char)> {
+    let caps = DMS_RE.captures(input.trim())?;
+
+    let degrees: f64 = caps[1].parse().ok()?;
+    let minutes: f64 = caps.get(2).map_or(Some(0.0), |m| m.as_str().parse().ok())?;
+    let seconds: f64 = caps.get(3).map_or(Some(0.0), |m| m.as_str().parse().ok())?;
+    let hemisphere = caps[4].chars().next()?.to_ascii_uppercase();
+
+    if minutes >= 60.0 || seconds >= 60.0 {
+        return None;
+    }
+
+    let decimal = degrees + minutes / 60.0 + seconds / 3600.0;
+    let limit = if matches!(hemisphere, 'N' | 'S') { 90.0 } else { 180.0 };
+
+    if decimal > limit {
+        return None;
+    }
+
+    Some((if matches!(hemisphere, 'S' | 'W') { -decimal } else { decimal }, hemisphere))
+}
+
+/// Parse a coordinate pair into decimal (latitude, longitude). Accepts decimal
+/// degrees separated by a comma ("40.446, -79.982") and, when `dms` is set, a
+/// DMS latitude followed by a DMS longitude separated by whitespace or a comma.
+pub fn parse_coordinates(input: &str, dms: bool) -> Option<(f64, f64)> {
+    let input = input.trim();
+
+    if let Some((lat, lon)) = input.split_once(',') {
+        if let (Ok(lat), Ok(lon)) = (lat.trim().parse::<f64>(), lon.trim().parse::<f64>()) {
+            let in_range = (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon);
+            return if in_range { Some((lat, lon)) } else { None };
+        }
+    }
+
+    if !dms {
+        return None;
+    }
+
+    // Split after the latitude's hemisphere letter
+    let split = input.find(['N', 'S', 'n', 's'])? + 1;
+    let (lat, lon) = input.split_at(split);
+
+    let (lat, lat_hemisphere) = parse_dms(lat)?;
+    let (lon, lon_hemisphere) = parse_dms(lon.trim_start_matches([',', ' ']))?;
+
+    if !matches!(lat_hemisphere, 'N' | 'S') || !matches!(lon_hemisphere, 'E' | 'W') {
+        return None;
+    }
+
+    Some((lat, lon))
+}
+
+// A latitude/longitude pair in decimal degrees, or in DMS with the "dms" arg
+pub fn geolocation(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let dms = request.args.iter().any(|a| a == DMS);
+
+    Ok(parse_coordinates(field.str(), dms).is_some())
+}
+
+// Default for geolocation_precise(): 4 decimal places is roughly 11m at the equator
+const MAX_COORDINATE_DECIMALS: u32 = 4;
+
+/// Implied precision of a decimal degrees coordinate pair, as the number of
+/// decimal places of its most precise half. Each place narrows the location
+/// roughly tenfold, 5 places being about 1m. None when the value isn't a
+/// decimal coordinate pair (see `parse_coordinates()`).
+pub fn coordinate_precision(input: &str) -> Option<u32> {
+    parse_coordinates(input, false)?;
+
+    let (lat, lon) = input.trim().split_once(',')?;
+
+    let decimals = |value: &str| match value.trim().split_once('.') {
+        Some((_, fraction)) => fraction.chars().take_while(|c| c.is_ascii_digit()).count() as u32,
+        None => 0,
+    };
+
+    Some(decimals(lat).max(decimals(lon)))
+}
+
+// Optional arg: maximum decimal places a coordinate may have, defaults to 4.
+// Matches decimal coordinate pairs precise enough to pinpoint a location.
+pub fn geolocation_precise(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let max_decimals = match request.args.first() {
+        Some(arg) => {
+            let max = parse_number(arg)?;
+
+            if max < 0.0 || max.fract() != 0.0 {
+                return Err(CustomError::Error(format!(
+                    "geolocation_precise max decimals must be a whole number, got {}",
+                    arg
+                )));
+            }
+
+            max as u32
+        }
+        None => MAX_COORDINATE_DECIMALS,
+    };
+
+    Ok(coordinate_precision(field.str()).is_some_and(|decimals| decimals > max_decimals))
 }
 
 // Intended to operate on the entire payload
@@ -168,3 +1125,137 @@ pub fn financial(_request: &Request, _field: Value) -> Result<bool, CustomError>
 pub fn health(_request: &Request, _field: Value) -> Result<bool, CustomError> {
     Err(CustomError::Error("not implemented".to_string()))
 }
+
+lazy_static! {
+    // (provider, pattern) - capture group 1 is the key itself. Patterns are bounded
+    // so a key embedded in a longer token (ie. part of a hash) doesn't match.
+    static ref CLOUD_KEY_PATTERNS: Vec<(&'static str, Regex)> = vec![
+        ("aws", Regex::new(r"\b((?:AKIA|ASIA|AGPA|AIDA|AROA|AIPA|ANPA|ANVA)[A-Z0-9]{16})\b").unwrap()),
+        ("gcp", Regex::new(r"\b(AIza[0-9A-Za-z_\-]{35})(?:[^0-9A-Za-z_\-]|$)").unwrap()),
+        ("gcp", Regex::new(r"\b(GOCSPX-[0-9A-Za-z_\-]{28})(?:[^0-9A-Za-z_\-]|$)").unwrap()),
+        ("azure", Regex::new(r"AccountKey=([A-Za-z0-9+/]{86}==)").unwrap()),
+    ];
+}
+
+/// A cloud provider credential found in a string. `start`/`end` are byte offsets.
+#[derive(Debug, PartialEq)]
+pub struct CloudKeyMatch {
+    pub provider: &'static str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find AWS access key ids, Google API keys/OAuth client secrets and Azure storage
+/// account keys in `value`
+pub fn find_cloud_keys(value: &str) -> Vec<CloudKeyMatch> {
+    let mut found: Vec<CloudKeyMatch> = CLOUD_KEY_PATTERNS
+        .iter()
+        .flat_map(|(provider, re)| {
+            re.captures_iter(value).filter_map(move |caps| {
+                caps.get(1).map(|key| CloudKeyMatch {
+                    provider,
+                    start: key.start(),
+                    end: key.end(),
+                })
+            })
+        })
+        .collect();
+
+    found.sort_by_key(|m| m.start);
+    found
+}
+
+pub fn cloud_key(_request: &Request, field: Value) -> Result<bool, CustomError> {
+    Ok(!find_cloud_keys(field.str()).is_empty())
+}
+
+/// Shannon entropy of `value` in bits per character. Random base64 approaches
+/// log2 of its length (at most 6), English words sit around 3 to 4.
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut counts: std::collections::HashMap<char, usize> = std::collections::HashMap::new();
+    let mut total = 0;
+
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+        total += 1;
+    }
+
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// A run of text with high entropy found by `find_high_entropy()`. `start`/`end`
+/// are byte offsets; `entropy` is the highest of the windows that make up the run.
+#[derive(Debug, PartialEq)]
+pub struct EntropySpan {
+    pub start: usize,
+    pub end: usize,
+    pub entropy: f64,
+}
+
+/// Slide a `window` character wide window over `value` and return the spans
+/// where its entropy (see `shannon_entropy()`) exceeds `threshold`. Overlapping
+/// windows are merged into one span. Windows containing whitespace are skipped,
+/// as secrets rarely do and prose would otherwise add noise.
+pub fn find_high_entropy(value: &str, window: usize, threshold: f64) -> Vec<EntropySpan> {
+    let chars: Vec<(usize, char)> = value.char_indices().collect();
+    let mut spans: Vec<EntropySpan> = Vec::new();
+
+    if window == 0 || chars.len() < window {
+        return spans;
+    }
+
+    for i in 0..=chars.len() - window {
+        let slice = &chars[i..i + window];
+
+        if slice.iter().any(|(_, c)| c.is_whitespace()) {
+            continue;
+        }
+
+        let start = slice[0].0;
+        let end = chars.get(i + window).map_or(value.len(), |(offset, _)| *offset);
+        let entropy = shannon_entropy(&value[start..end]);
+
+        if entropy <= threshold {
+            continue;
+        }
+
+        match spans.last_mut() {
+            Some(last) if start < last.end => {
+                last.end = end;
+                last.entropy = last.entropy.max(entropy);
+            }
+            _ => spans.push(EntropySpan {
+                start,
+                end,
+                entropy,
+            }),
+        }
+    }
+
+    spans
+}
+
+// Optional args: window length in characters (default 20) and entropy threshold
+// in bits per character (default 4.0). Matches when any window of the field is
+// above the threshold, ie. a token or key embedded in a log line.
+pub fn high_entropy_substring(request: &Request, field: Value) -> Result<bool, CustomError> {
+    let window = match request.args.first() {
+        Some(arg) => arg.trim().parse::<usize>().ok().filter(|w| *w > 0).ok_or_else(|| {
+            CustomError::InvalidArgument(format!("window '{}' is not a positive integer", arg))
+        })?,
+        None => 20,
+    };
+
+    let threshold = match request.args.get(1) {
+        Some(arg) => parse_number(arg)?,
+        None => 4.0,
+    };
+
+    Ok(!find_high_entropy(field.str(), window, threshold).is_empty())
+}
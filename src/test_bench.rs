@@ -199,3 +199,93 @@ fn bench_credit_card_payload(b: &mut Bencher) {
     });
 }
 
+// Patterns for comparing a RegexSet against running each pattern on its own.
+// None of them match, so every pattern has to be tried.
+fn regex_bench_patterns() -> Vec<String> {
+    (0..20).map(|i| format!(r"^prefix-{}-[a-z]+\d{{3}}$", i)).collect()
+}
+
+#[bench]
+fn bench_regex_separate(b: &mut Bencher) {
+    let patterns = regex_bench_patterns();
+    let request = generate_request_for_bench(
+        DetectiveType::DETECTIVE_TYPE_REGEX,
+        "object.field",
+        vec![],
+    );
+
+    b.iter(|| {
+        let field = parse_field(request.data, &request.path).unwrap();
+        patterns.iter().any(|pattern| {
+            crate::matcher_core::cached_regex(pattern).unwrap().is_match(field.str())
+        })
+    });
+}
+
+#[bench]
+fn bench_regex_set(b: &mut Bencher) {
+    let request = generate_request_for_bench(
+        DetectiveType::DETECTIVE_TYPE_REGEX,
+        "object.field",
+        regex_bench_patterns(),
+    );
+
+    b.iter(|| {
+        let field = parse_field(request.data, &request.path).unwrap();
+        let _ = crate::matcher_core::regex(&request, field);
+    });
+}
+
+fn document_bench_rules() -> Vec<(DetectiveType, String, Vec<String>)> {
+    vec![
+        (
+            DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+            "object.field".to_string(),
+            vec!["value".to_string()],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_IPV4_ADDRESS,
+            "object.ipv4_address".to_string(),
+            vec![],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_NUMERIC_GREATER_THAN,
+            "number_int".to_string(),
+            vec!["10".to_string()],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_PII_EMAIL,
+            "object.email_real".to_string(),
+            vec![],
+        ),
+        (
+            DetectiveType::DETECTIVE_TYPE_HAS_FIELD,
+            "object.semver".to_string(),
+            vec![],
+        ),
+    ]
+}
+
+#[bench]
+fn bench_many_rules_single_calls(b: &mut Bencher) {
+    let det = crate::detective::Detective::new();
+    let rules = document_bench_rules();
+
+    b.iter(|| {
+        for (match_type, path, args) in &rules {
+            let request = generate_request_for_bench(*match_type, path, args.clone());
+            let _ = det.matches(&request);
+        }
+    });
+}
+
+#[bench]
+fn bench_many_rules_document(b: &mut Bencher) {
+    let det = crate::detective::Detective::new();
+    let rules = document_bench_rules();
+    let doc = crate::detective::Document::parse(&crate::test_utils::SAMPLE_JSON_BYTES).unwrap();
+
+    b.iter(|| {
+        let _ = det.matches_document(&doc, &rules);
+    });
+}
@@ -0,0 +1,104 @@
+use crate::error::CustomError;
+use base64::Engine;
+
+/// Args starting with this prefix name a transform to apply to the value at
+/// the request path before the matcher runs, ie. `transform:url_decode`.
+/// Transforms run in the order they appear in the args and are removed from
+/// the args before they reach the matcher.
+pub const TRANSFORM_PREFIX: &str = "transform:";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    Lowercase,
+    Uppercase,
+    Trim,
+    /// Standard or URL-safe alphabet, padded or not. The decoded bytes must be UTF-8.
+    Base64Decode,
+    /// `%XX` percent escapes. `+` is left as-is.
+    UrlDecode,
+}
+
+impl Transform {
+    pub fn parse(name: &str) -> Result<Transform, CustomError> {
+        match name {
+            "lowercase" => Ok(Transform::Lowercase),
+            "uppercase" => Ok(Transform::Uppercase),
+            "trim" => Ok(Transform::Trim),
+            "base64_decode" => Ok(Transform::Base64Decode),
+            "url_decode" => Ok(Transform::UrlDecode),
+            _ => Err(CustomError::InvalidArgument(format!(
+                "unknown transform '{}', expected one of lowercase, uppercase, trim, base64_decode, url_decode",
+                name
+            ))),
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String, CustomError> {
+        match self {
+            Transform::Lowercase => Ok(value.to_lowercase()),
+            Transform::Uppercase => Ok(value.to_uppercase()),
+            Transform::Trim => Ok(value.trim().to_string()),
+            Transform::Base64Decode => base64_decode(value),
+            Transform::UrlDecode => url_decode(value),
+        }
+    }
+}
+
+/// Split the transforms out of `args`, returning them in order along with the
+/// remaining args.
+pub fn split_args(args: &[String]) -> Result<(Vec<Transform>, Vec<String>), CustomError> {
+    let mut transforms = Vec::new();
+    let mut rest = Vec::new();
+
+    for arg in args {
+        match arg.strip_prefix(TRANSFORM_PREFIX) {
+            Some(name) => transforms.push(Transform::parse(name)?),
+            None => rest.push(arg.clone()),
+        }
+    }
+
+    Ok((transforms, rest))
+}
+
+/// Run `transforms` over `value` in order.
+pub fn apply_all(transforms: &[Transform], value: &str) -> Result<String, CustomError> {
+    transforms
+        .iter()
+        .try_fold(value.to_string(), |value, transform| transform.apply(&value))
+}
+
+fn base64_decode(value: &str) -> Result<String, CustomError> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+
+    let decoded = [STANDARD, URL_SAFE, STANDARD_NO_PAD, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(value.trim()).ok())
+        .ok_or_else(|| CustomError::InvalidField(format!("'{}' is not valid base64", value)))?;
+
+    String::from_utf8(decoded)
+        .map_err(|_| CustomError::InvalidField(format!("base64 '{}' does not decode to UTF-8", value)))
+}
+
+fn url_decode(value: &str) -> Result<String, CustomError> {
+    let invalid = || CustomError::InvalidField(format!("'{}' has an invalid percent escape", value));
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|_| CustomError::InvalidField(format!("'{}' does not decode to UTF-8", value)))
+}
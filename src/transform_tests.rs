@@ -0,0 +1,124 @@
+use crate::detective::Request;
+use crate::transform::{apply_all, split_args, Transform};
+use protos::sp_steps_detective::DetectiveType;
+
+#[test]
+fn test_transform_apply() {
+    let cases = vec![
+        (Transform::Lowercase, "Hello WORLD", "hello world"),
+        (Transform::Uppercase, "Hello world", "HELLO WORLD"),
+        (Transform::Trim, "  padded\n", "padded"),
+        (Transform::Base64Decode, "aGVsbG8gd29ybGQ=", "hello world"),
+        (Transform::Base64Decode, "aGVsbG8gd29ybGQ", "hello world"),
+        (Transform::UrlDecode, "a%20b%2Fc+d", "a b/c+d"),
+        (Transform::UrlDecode, "caf%C3%A9", "café"),
+    ];
+
+    for (transform, input, expected) in cases {
+        assert_eq!(transform.apply(input).unwrap(), expected, "{:?} {}", transform, input);
+    }
+
+    assert!(Transform::Base64Decode.apply("not base64!").is_err());
+    assert!(Transform::Base64Decode.apply("/w==").is_err(), "non UTF-8 should error");
+    assert!(Transform::UrlDecode.apply("100%").is_err());
+    assert!(Transform::UrlDecode.apply("%zz").is_err());
+    assert!(Transform::parse("reverse").is_err());
+}
+
+#[test]
+fn test_transform_split_args() {
+    let args: Vec<String> = ["transform:url_decode", "delivered", "transform:lowercase"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+
+    let (transforms, rest) = split_args(&args).unwrap();
+
+    assert_eq!(transforms, vec![Transform::UrlDecode, Transform::Lowercase]);
+    assert_eq!(rest, vec!["delivered".to_string()]);
+
+    // Order matters: decoding after lowercasing would lowercase the escapes too
+    assert_eq!(apply_all(&transforms, "%41BC").unwrap(), "abc");
+    assert_eq!(apply_all(&[], "ABC").unwrap(), "ABC");
+
+    assert!(split_args(&["transform:nope".to_string()]).is_err());
+}
+
+#[test]
+fn test_transform_matches() {
+    let sample_json = &crate::test_utils::SAMPLE_JSON.as_bytes().to_vec();
+
+    let test_cases = vec![
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "object.url_encoded_status".to_string(),
+                args: vec![
+                    "transform:url_decode".to_string(),
+                    "transform:lowercase".to_string(),
+                    "transform:trim".to_string(),
+                    "delivered/closed".to_string(),
+                ],
+                negate: false,
+            },
+            expected: true,
+            text: "url decoded, lowercased and trimmed value should equal".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "object.url_encoded_status".to_string(),
+                args: vec!["delivered/closed".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "raw value should not equal without transforms".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "object.url_encoded_status".to_string(),
+                args: vec![
+                    "transform:url_decode".to_string(),
+                    "transform:lowercase".to_string(),
+                    "delivered/closed".to_string(),
+                ],
+                negate: true,
+            },
+            expected: true,
+            text: "negated match against the untrimmed value should be true".to_string(),
+            should_error: false,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "object.field".to_string(),
+                args: vec!["transform:base64_decode".to_string(), "value".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "failing transform should error".to_string(),
+            should_error: true,
+        },
+        crate::test_utils::TestCase {
+            request: Request {
+                match_type: DetectiveType::DETECTIVE_TYPE_STRING_EQUAL,
+                data: sample_json,
+                path: "object.field".to_string(),
+                args: vec!["transform:reverse".to_string(), "value".to_string()],
+                negate: false,
+            },
+            expected: false,
+            text: "unknown transform should error".to_string(),
+            should_error: true,
+        },
+    ];
+
+    crate::test_utils::run_tests(&test_cases);
+}